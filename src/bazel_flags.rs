@@ -4,6 +4,7 @@ use prost::Message;
 use std::{collections::HashMap, io::Cursor, process::Command};
 
 use crate::bazel_flags_proto::{FlagCollection, FlagInfo};
+use crate::flag_types::{FlagValueType, FLAG_VALUE_TYPES};
 
 pub static COMMAND_DOCS: phf::Map<&'static str, &'static str> = phf_map! {
     // The command line docs, taken from the `bazel help`
@@ -40,7 +41,11 @@ pub static COMMAND_DOCS: phf::Map<&'static str, &'static str> = phf_map! {
 
 #[derive(Debug)]
 pub struct BazelFlags {
+    // The Bazel version the flags were loaded for, if known
+    pub bazel_version: Option<String>,
     pub commands: Vec<String>,
+    // The commands to which `always` applies, i.e. all commands except `startup`
+    pub always_commands: Vec<String>,
     pub flags: Vec<FlagInfo>,
     pub flags_by_commands: HashMap<String, Vec<usize>>,
     pub flags_by_name: HashMap<String, usize>,
@@ -79,6 +84,13 @@ impl BazelFlags {
             }
         }
 
+        let mut always_commands = flags_by_commands
+            .keys()
+            .filter(|c| *c != "startup")
+            .cloned()
+            .collect::<Vec<_>>();
+        always_commands.sort();
+
         // The `common` option is the union of all other options
         let mut common_flags = flags_by_commands
             .values()
@@ -92,7 +104,8 @@ impl BazelFlags {
         // For safe usage, the `always` option should be the intersection of all other options.
         // Using an option not supported by all commands would otherwise make some commands
         // unusable. But there are no options which are valid for *all* commands.
-        // Hence, I am using the union of all flags.
+        // Hence, I am using the union of all flags, and flags which are not supported by
+        // all commands are diagnosed separately (see `FlagInfo::get_unsupported_commands`).
         flags_by_commands.insert("always".to_string(), common_flags);
 
         // Determine the list of supported commands
@@ -100,7 +113,9 @@ impl BazelFlags {
         commands.extend(["import".to_string(), "try-import".to_string()]);
 
         BazelFlags {
+            bazel_version: bazel_version.map(str::to_string),
             commands,
+            always_commands,
             flags,
             flags_by_commands,
             flags_by_name,
@@ -115,8 +130,12 @@ impl BazelFlags {
             if long_name.starts_with('-') {
                 return None;
             }
-            // Strip the `no` prefix, if any
-            let stripped_no = long_name.strip_prefix("no").unwrap_or(long_name);
+            // Strip the `no` prefix, if any. Flag names which themselves start with `no` take precedence.
+            let stripped_no = if self.flags_by_name.contains_key(long_name) {
+                long_name
+            } else {
+                long_name.strip_prefix("no").unwrap_or(long_name)
+            };
             return self.flags_by_name.get(stripped_no).map(|i| {
                 let flag = self.flags.get(*i).unwrap();
                 let old_name =
@@ -141,6 +160,55 @@ impl BazelFlags {
         }
         None
     }
+
+    // The hover documentation for a flag used on a line with the given command.
+    // On `always`, this also lists the commands which would fail because of the flag.
+    pub fn get_hover_markdown(&self, flag_info: &FlagInfo, command: Option<&str>) -> String {
+        let mut content = flag_info.get_documentation_markdown();
+        if command == Some("always") {
+            let unsupported = flag_info.get_unsupported_commands(&self.always_commands);
+            if !unsupported.is_empty() {
+                content += format!(
+                    "\nFails with `always` for: {}\n",
+                    unsupported
+                        .iter()
+                        .map(|c| format!("`{}`", c))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+                .as_str();
+            }
+        }
+        content
+    }
+
+    // Finds the flags whose name or documentation contains `query`, ignoring case.
+    // Flags matching by name come first.
+    pub fn search(&self, query: &str) -> Vec<&FlagInfo> {
+        let query = query.to_lowercase();
+        let mut indices = self.flags_by_name.values().copied().collect::<Vec<_>>();
+        indices.sort();
+        indices.dedup();
+        let mut matches = indices
+            .into_iter()
+            .map(|i| &self.flags[i])
+            .filter_map(|f| {
+                if f.name.to_lowercase().contains(&query) {
+                    Some((0, f))
+                } else if f
+                    .documentation
+                    .as_ref()
+                    .is_some_and(|d| d.to_lowercase().contains(&query))
+                {
+                    Some((1, f))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        matches.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.name.cmp(&b.1.name)));
+        matches.into_iter().map(|(_, f)| f).collect()
+    }
 }
 
 pub fn load_packaged_bazel_flag_collection() -> FlagCollection {
@@ -157,6 +225,18 @@ pub fn load_packaged_bazel_flags(bazel_version: &str) -> BazelFlags {
     )
 }
 
+// A boolean `build` flag which is available in the given Bazel versions, for tests using synthetic flags
+#[cfg(test)]
+pub fn test_flag_info(name: &str, bazel_versions: &[&str]) -> FlagInfo {
+    FlagInfo {
+        name: name.to_string(),
+        commands: vec!["build".to_string()],
+        has_negative_flag: Some(true),
+        bazel_versions: bazel_versions.iter().map(|v| v.to_string()).collect(),
+        ..Default::default()
+    }
+}
+
 pub fn load_bazel_flags_from_command(bazel_command: &str) -> Result<BazelFlags, String> {
     let result = Command::new(bazel_command)
         // Disable bazelrc loading. Otherwise, with an invalid bazelrc, the `bazel help`
@@ -279,6 +359,31 @@ impl FlagInfo {
         command == "common" || command == "always" || self.commands.iter().any(|c| c == command)
     }
 
+    // The commands which don't support this flag, out of the given commands.
+    // Used to find the commands which would fail if the flag is used with `always`.
+    pub fn get_unsupported_commands<'a>(&self, commands: &'a [String]) -> Vec<&'a str> {
+        commands
+            .iter()
+            .filter(|c| !self.commands.contains(c))
+            .map(String::as_str)
+            .collect()
+    }
+
+    // Checks if the flag was invoked through its negative form, e.g. `--nokeep_going`
+    pub fn is_negated_by(&self, invocation: &str) -> bool {
+        let stripped = invocation.strip_suffix('=').unwrap_or(invocation);
+        stripped
+            .strip_prefix("--no")
+            .is_some_and(|n| n == self.name || self.old_name.as_deref() == Some(n))
+    }
+
+    // The expected value type, if known. Bazel only offers negative forms for booleans and tri-states.
+    pub fn value_type(&self) -> Option<FlagValueType> {
+        FLAG_VALUE_TYPES.get(self.name.as_str()).copied().or(self
+            .has_negative_flag()
+            .then_some(FlagValueType::BooleanOrTriState))
+    }
+
     pub fn get_documentation_markdown(&self) -> String {
         let mut result = String::new();
 
@@ -327,6 +432,25 @@ impl FlagInfo {
     }
 }
 
+#[test]
+fn test_search() {
+    let flags = load_packaged_bazel_flags("8.0.0");
+    let names = |query: &str| {
+        flags
+            .search(query)
+            .iter()
+            .map(|f| f.name.as_str())
+            .collect::<Vec<_>>()
+    };
+    let remote_cache = names("REMOTE_CACHE");
+    assert!(remote_cache.contains(&"remote_cache"));
+    assert!(remote_cache.iter().all(|n| n.contains("remote_cache")));
+    // Documentation matches come after name matches
+    let keep_going = names("keep_going");
+    assert_eq!(keep_going[0], "keep_going");
+    assert!(names("no such flag or documentation").is_empty());
+}
+
 #[test]
 fn test_flags() {
     let flags = load_packaged_bazel_flags("7.1.0");
@@ -363,6 +487,30 @@ fn test_flags() {
         flags.get_by_invocation("--keep_going").unwrap().1
     );
 
+    // Negated flags are found, and can be identified as such
+    let keep_going = flags.get_by_invocation("--keep_going").unwrap().1;
+    assert_eq!(
+        flags.get_by_invocation("--nokeep_going").unwrap().1,
+        keep_going
+    );
+    assert!(keep_going.is_negated_by("--nokeep_going"));
+    assert!(!keep_going.is_negated_by("--keep_going"));
+
+    // `always` applies to all commands, but `keep_going` is not supported by all of them
+    assert!(!flags.always_commands.contains(&"startup".to_string()));
+    let unsupported = keep_going.get_unsupported_commands(&flags.always_commands);
+    assert!(unsupported.contains(&"version"));
+    assert!(!unsupported.contains(&"build"));
+    // ... which is shown in the hover for `always` lines only
+    let always_hover = flags.get_hover_markdown(keep_going, Some("always"));
+    assert!(always_hover.starts_with(&keep_going.get_documentation_markdown()));
+    assert!(always_hover.contains("\nFails with `always` for: "));
+    assert!(always_hover.contains("`version`"));
+    assert_eq!(
+        flags.get_hover_markdown(keep_going, Some("build")),
+        keep_going.get_documentation_markdown()
+    );
+
     // The `remote_cache` is valid for at least one command. Hence, it should be in `common`.
     assert!(flags
         .flags_by_commands
@@ -390,3 +538,27 @@ fn test_flag_versions() {
     assert!(!bazel8_flags.flags_by_name.contains_key("python3_path"));
     assert!(!bazel9_flags.flags_by_name.contains_key("python3_path"));
 }
+
+#[test]
+fn test_value_type() {
+    // Flags with a negative form might also be tri-state flags, accepting `auto`.
+    // Hence, only flags which are known to be booleans are treated as such.
+    // Without a negative form, only the listed flags have a known type.
+    assert_eq!(
+        test_flag_info("keep_going", &["8.0.0"]).value_type(),
+        Some(FlagValueType::Boolean)
+    );
+    assert_eq!(
+        test_flag_info("some_tristate_flag", &["8.0.0"]).value_type(),
+        Some(FlagValueType::BooleanOrTriState)
+    );
+    let string_flag = FlagInfo {
+        has_negative_flag: Some(false),
+        ..test_flag_info("some_string_flag", &["8.0.0"])
+    };
+    assert_eq!(string_flag.value_type(), None);
+    assert_eq!(
+        test_flag_info("cache_test_results", &["8.0.0"]).value_type(),
+        Some(FlagValueType::TriState)
+    );
+}
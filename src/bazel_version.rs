@@ -96,6 +96,12 @@ pub fn find_closest_version(
     }
 }
 
+// Sort Bazel versions from oldest to newest, removing duplicates
+pub fn sort_bazel_versions(versions: &mut Vec<String>) {
+    versions.sort_by_cached_key(|s| parse_bazel_version(s).map(|v| v.as_tuple()));
+    versions.dedup();
+}
+
 // Use the Bazelisk logic to figure out the Bazel version
 // Ref: https://github.com/bazelbuild/bazelisk/blob/1f9a1aca958cdb50b4adb84b15cdda55a600ed31/README.md?plain=1#L45-L47
 pub fn determine_bazelisk_version(path: &Path) -> Option<String> {
@@ -215,6 +221,18 @@ fn test_parse_bazel_version() {
     );
 }
 
+#[test]
+fn test_sort_bazel_versions() {
+    let mut versions = ["8.0.0", "10.0.0-pre.20260120.1", "7.10.0", "7.2.0", "8.0.0"]
+        .map(|s| s.to_string())
+        .to_vec();
+    sort_bazel_versions(&mut versions);
+    assert_eq!(
+        versions,
+        vec!["7.2.0", "7.10.0", "8.0.0", "10.0.0-pre.20260120.1"]
+    );
+}
+
 #[test]
 fn test_find_closest_version() {
     let versions = [
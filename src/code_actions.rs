@@ -0,0 +1,141 @@
+use std::{collections::HashMap, ops::Range};
+
+use ropey::Rope;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, Diagnostic, TextEdit, Url, WorkspaceEdit,
+};
+
+use crate::lsp_utils::{decode_lsp_pos, LspPositionEncoding};
+
+// A fix for a diagnostic. The fixes are stored in the `data` field of the diagnostic,
+// such that we don't need to recompute them when the client requests code actions.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuickFix {
+    pub title: String,
+    pub edits: Vec<TextEdit>,
+    // Preferred fixes are safe to apply automatically, e.g. through `lint --fix`.
+    // Fixes which are only guesses, like typo suggestions, are not preferred.
+    #[serde(default)]
+    pub is_preferred: bool,
+}
+
+pub fn with_quick_fixes(mut diagnostic: Diagnostic, fixes: Vec<QuickFix>) -> Diagnostic {
+    diagnostic.data = Some(serde_json::to_value(fixes).unwrap());
+    diagnostic
+}
+
+pub fn get_quick_fixes(diagnostic: &Diagnostic) -> Vec<QuickFix> {
+    diagnostic
+        .data
+        .as_ref()
+        .and_then(|data| serde_json::from_value(data.clone()).ok())
+        .unwrap_or_default()
+}
+
+// Gets the code actions for the diagnostics the client sent us
+pub fn get_code_actions(uri: &Url, diagnostics: &[Diagnostic]) -> Vec<CodeActionOrCommand> {
+    diagnostics
+        .iter()
+        .flat_map(|diagnostic| {
+            get_quick_fixes(diagnostic).into_iter().map(move |fix| {
+                CodeActionOrCommand::CodeAction(CodeAction {
+                    title: fix.title,
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    diagnostics: Some(vec![diagnostic.clone()]),
+                    edit: Some(WorkspaceEdit {
+                        changes: Some(HashMap::from([(uri.clone(), fix.edits)])),
+                        ..Default::default()
+                    }),
+                    is_preferred: Some(fix.is_preferred),
+                    ..Default::default()
+                })
+            })
+        })
+        .collect()
+}
+
+// Applies the preferred fix of each diagnostic to `text`.
+// Fixes overlapping with an earlier fix are skipped. Returns the number of applied fixes.
+pub fn apply_preferred_fixes(
+    text: &mut String,
+    diagnostics: &[Diagnostic],
+    encoding: LspPositionEncoding,
+) -> usize {
+    let rope = Rope::from_str(text);
+    // The edits of each fix, as byte ranges
+    let mut fixes = diagnostics
+        .iter()
+        .filter_map(|d| get_quick_fixes(d).into_iter().find(|f| f.is_preferred))
+        .filter_map(|fix| {
+            fix.edits
+                .into_iter()
+                .map(|e| {
+                    let start = decode_lsp_pos(&rope, &e.range.start, encoding)?;
+                    let end = decode_lsp_pos(&rope, &e.range.end, encoding)?;
+                    Some((start..end, e.new_text))
+                })
+                .collect::<Option<Vec<_>>>()
+        })
+        .collect::<Vec<_>>();
+    fixes.sort_by_key(|edits| edits.iter().map(|e| e.0.start).min());
+
+    // Collect the edits of all non-overlapping fixes
+    let mut accepted_edits = Vec::<(Range<usize>, String)>::new();
+    let mut applied_fixes = 0;
+    for edits in fixes {
+        // Edits starting at the same position also conflict, e.g. two insertions
+        let overlaps = edits.iter().any(|(range, _)| {
+            accepted_edits.iter().any(|(other, _)| {
+                (range.start < other.end && other.start < range.end) || range.start == other.start
+            })
+        });
+        if overlaps {
+            continue;
+        }
+        accepted_edits.extend(edits);
+        applied_fixes += 1;
+    }
+
+    // Apply the edits back to front, so that the offsets stay valid
+    accepted_edits.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+    for (range, new_text) in accepted_edits {
+        text.replace_range(range, &new_text);
+    }
+    applied_fixes
+}
+
+#[test]
+fn test_apply_preferred_fixes() {
+    use tower_lsp::lsp_types::Position;
+    let fix = |title: &str, line: u32, start: u32, end: u32, new_text: &str, is_preferred| {
+        with_quick_fixes(
+            Diagnostic::default(),
+            vec![QuickFix {
+                title: title.to_string(),
+                edits: vec![TextEdit {
+                    range: tower_lsp::lsp_types::Range::new(
+                        Position::new(line, start),
+                        Position::new(line, end),
+                    ),
+                    new_text: new_text.to_string(),
+                }],
+                is_preferred,
+            }],
+        )
+    };
+    let mut text = "build -k --nofoo=1\ntest --bar\n".to_string();
+    let diagnostics = vec![
+        fix("Expand", 0, 6, 8, "--keep_going", true),
+        fix("Negate", 0, 9, 18, "--foo=false", true),
+        // Overlaps with the previous fix
+        fix("Overlapping", 0, 9, 16, "--baz", true),
+        // Only a guess
+        fix("Guess", 1, 5, 10, "--baz", false),
+    ];
+    assert_eq!(
+        apply_preferred_fixes(&mut text, &diagnostics, LspPositionEncoding::UTF32),
+        2
+    );
+    assert_eq!(text, "build --keep_going --foo=false\ntest --bar\n");
+}
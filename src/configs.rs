@@ -0,0 +1,220 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::json;
+
+use crate::rc_order::{flatten_imports, RcFile};
+
+// A config, i.e. a name usable with `--config`, together with where it is defined
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigInfo {
+    pub name: String,
+    // The commands for which the config has lines, in order of their first appearance
+    pub commands: Vec<String>,
+    // The files which define the config, in the order in which Bazel reads them
+    pub files: Vec<PathBuf>,
+    // The comment block immediately preceding the first definition
+    pub description: Option<String>,
+}
+
+// Collects all configs defined in the given bazelrc files and their imports, sorted by name
+pub fn collect_configs(rc_files: &[RcFile]) -> Vec<ConfigInfo> {
+    let mut configs = Vec::<ConfigInfo>::new();
+    for rc_file in rc_files {
+        let root_path = rc_file
+            .imported
+            .root_path
+            .clone()
+            .unwrap_or(rc_file.path.clone());
+        // The comment lines directly above the current line, and the file containing them
+        let mut comment_block = Vec::<&str>::new();
+        let mut comment_file = None;
+        for rc_line in flatten_imports(&rc_file.lines, &rc_file.imported) {
+            let line = rc_line.line;
+            let path = rc_line.file.map(|f| &f.path).unwrap_or(&root_path);
+            if line.command.is_none() && line.flags.is_empty() {
+                match &line.comment {
+                    Some(comment) => {
+                        if comment_file != Some(path) {
+                            comment_block.clear();
+                            comment_file = Some(path);
+                        }
+                        comment_block.push(comment.0.trim());
+                    }
+                    // Empty lines separate comment blocks
+                    None => comment_block.clear(),
+                }
+                continue;
+            }
+            let preceding_comment = if comment_file == Some(path) {
+                std::mem::take(&mut comment_block)
+            } else {
+                comment_block.clear();
+                Vec::new()
+            };
+            let (Some(command), Some(config)) = (&line.command, &line.config) else {
+                continue;
+            };
+            let config_info = match configs.iter_mut().find(|c| c.name == config.0) {
+                Some(config_info) => config_info,
+                None => {
+                    let description = preceding_comment
+                        .into_iter()
+                        .filter(|l| !l.is_empty())
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    configs.push(ConfigInfo {
+                        name: config.0.clone(),
+                        commands: Vec::new(),
+                        files: Vec::new(),
+                        description: Some(description).filter(|d| !d.is_empty()),
+                    });
+                    configs.last_mut().unwrap()
+                }
+            };
+            if !config_info.commands.contains(&command.0) {
+                config_info.commands.push(command.0.clone());
+            }
+            if !config_info.files.contains(path) {
+                config_info.files.push(path.clone());
+            }
+        }
+    }
+    configs.sort_by(|a, b| a.name.cmp(&b.name));
+    configs
+}
+
+fn display_files(config: &ConfigInfo, base_dir: &Path) -> String {
+    config
+        .files
+        .iter()
+        .map(|p| p.strip_prefix(base_dir).unwrap_or(p).display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// The description on a single line, for tabular output
+fn single_line_description(config: &ConfigInfo) -> String {
+    config
+        .description
+        .clone()
+        .unwrap_or_default()
+        .replace('\n', " ")
+}
+
+// A plain-text table with aligned columns. File paths are shown relative to `base_dir`.
+pub fn format_table(configs: &[ConfigInfo], base_dir: &Path) -> String {
+    let header = ["CONFIG", "COMMANDS", "FILES", "DESCRIPTION"].map(str::to_string);
+    let mut rows = vec![header];
+    rows.extend(configs.iter().map(|c| {
+        [
+            c.name.clone(),
+            c.commands.join(", "),
+            display_files(c, base_dir),
+            single_line_description(c),
+        ]
+    }));
+    let widths = (0..3)
+        .map(|col| rows.iter().map(|r| r[col].chars().count()).max().unwrap())
+        .collect::<Vec<_>>();
+    let mut out = String::new();
+    for row in rows {
+        for (col, width) in widths.iter().enumerate() {
+            out += &format!("{:width$}  ", row[col], width = width);
+        }
+        out += &row[3];
+        out = out.trim_end().to_string() + "\n";
+    }
+    out
+}
+
+pub fn format_markdown(configs: &[ConfigInfo], base_dir: &Path) -> String {
+    let escape = |s: &str| s.replace('|', "\\|");
+    let mut out = "| Config | Commands | Files | Description |\n".to_string();
+    out += "|---|---|---|---|\n";
+    for c in configs {
+        out += &format!(
+            "| `{}` | {} | {} | {} |\n",
+            c.name,
+            c.commands.join(", "),
+            escape(&display_files(c, base_dir)),
+            escape(&single_line_description(c))
+        );
+    }
+    out
+}
+
+pub fn format_json(configs: &[ConfigInfo]) -> String {
+    let entries = configs
+        .iter()
+        .map(|c| {
+            json!({
+                "name": c.name,
+                "commands": c.commands,
+                "files": c.files,
+                "description": c.description,
+            })
+        })
+        .collect::<Vec<_>>();
+    serde_json::to_string_pretty(&entries).unwrap()
+}
+
+#[test]
+fn test_collect_configs() {
+    let bazel_flags = crate::bazel_flags::load_packaged_bazel_flags("8.0.0");
+    let examples =
+        std::fs::canonicalize(Path::new(env!("CARGO_MANIFEST_DIR")).join("examples")).unwrap();
+    let rc_file = RcFile::parse(
+        &examples.join("configs.bazelrc"),
+        "# Settings for CI.
+# Use together with `--config=remote`.
+build:ci --keep_going
+test:ci --test_output=errors
+
+# Not a description, because of the empty line
+
+build:remote --jobs=100
+# Not a description, because it follows the first definition
+build:remote --jobs=200
+build:debug -c dbg # Inline comments are no descriptions
+",
+        &bazel_flags,
+    );
+    let configs = collect_configs(&[rc_file]);
+    let summary = configs
+        .iter()
+        .map(|c| {
+            (
+                c.name.as_str(),
+                c.commands.join(","),
+                c.description.as_deref(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        vec![
+            (
+                "ci",
+                "build,test".to_string(),
+                Some("Settings for CI.\nUse together with `--config=remote`.")
+            ),
+            ("debug", "build".to_string(), None),
+            ("remote", "build".to_string(), None),
+        ]
+    );
+    assert_eq!(
+        format_table(&configs, &examples),
+        "CONFIG  COMMANDS     FILES            DESCRIPTION
+ci      build, test  configs.bazelrc  Settings for CI. Use together with `--config=remote`.
+debug   build        configs.bazelrc
+remote  build        configs.bazelrc
+"
+    );
+    assert_eq!(
+        format_markdown(&configs[..1], &examples),
+        "| Config | Commands | Files | Description |
+|---|---|---|---|
+| `ci` | build, test | configs.bazelrc | Settings for CI. Use together with `--config=remote`. |
+"
+    );
+}
@@ -0,0 +1,161 @@
+// The number of unchanged lines shown around each change
+const CONTEXT_LINES: usize = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DiffOp {
+    Equal,
+    Delete,
+    Insert,
+}
+
+// Computes a line-based diff using the longest common subsequence
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(DiffOp, &'a str)> {
+    // lcs[i][j] is the length of the longest common subsequence of `old[i..]` and `new[j..]`
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let mut ops = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            ops.push((DiffOp::Equal, old[i]));
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            ops.push((DiffOp::Delete, old[i]));
+            i += 1;
+        } else {
+            ops.push((DiffOp::Insert, new[j]));
+            j += 1;
+        }
+    }
+    ops
+}
+
+// Renders the changes between `old` and `new` in the unified diff format, as used by `diff -u`.
+// Returns an empty string if there are no changes.
+pub fn unified_diff(old: &str, new: &str, old_name: &str, new_name: &str) -> String {
+    let old_lines = old.split_inclusive('\n').collect::<Vec<_>>();
+    let new_lines = new.split_inclusive('\n').collect::<Vec<_>>();
+    let ops = diff_lines(&old_lines, &new_lines);
+
+    // Group the changes into hunks, merging hunks whose context would overlap
+    let mut hunks = Vec::<(usize, usize)>::new();
+    for (i, _) in ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| op.0 != DiffOp::Equal)
+    {
+        let start = i.saturating_sub(CONTEXT_LINES);
+        let end = (i + 1 + CONTEXT_LINES).min(ops.len());
+        match hunks.last_mut() {
+            Some(last) if last.1 >= start => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+    if hunks.is_empty() {
+        return String::new();
+    }
+
+    let mut out = format!("--- {}\n+++ {}\n", old_name, new_name);
+    // The line numbers at the start of each operation
+    let mut old_line_nr = 0;
+    let mut new_line_nr = 0;
+    let mut op_index = 0;
+    for (start, end) in hunks {
+        for (op, _) in &ops[op_index..start] {
+            old_line_nr += usize::from(*op != DiffOp::Insert);
+            new_line_nr += usize::from(*op != DiffOp::Delete);
+        }
+        let hunk_ops = &ops[start..end];
+        let old_count = hunk_ops.iter().filter(|o| o.0 != DiffOp::Insert).count();
+        let new_count = hunk_ops.iter().filter(|o| o.0 != DiffOp::Delete).count();
+        // Empty ranges are identified by the line before them
+        let old_start = old_line_nr + usize::from(old_count != 0);
+        let new_start = new_line_nr + usize::from(new_count != 0);
+        out += &format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start, old_count, new_start, new_count
+        );
+        for (op, line) in hunk_ops {
+            out.push(match op {
+                DiffOp::Equal => ' ',
+                DiffOp::Delete => '-',
+                DiffOp::Insert => '+',
+            });
+            out += line;
+            if !line.ends_with('\n') {
+                out += "\n\\ No newline at end of file\n";
+            }
+            old_line_nr += usize::from(*op != DiffOp::Insert);
+            new_line_nr += usize::from(*op != DiffOp::Delete);
+        }
+        op_index = end;
+    }
+    out
+}
+
+#[test]
+fn test_unified_diff() {
+    assert_eq!(unified_diff("a\nb\n", "a\nb\n", "old", "new"), "");
+    assert_eq!(
+        unified_diff("a\nb\nc\n", "a\nB\nc\n", "old", "new"),
+        "--- old\n+++ new\n@@ -1,3 +1,3 @@\n a\n-b\n+B\n c\n"
+    );
+    // Distant changes end up in separate hunks
+    let old = (1..=20).map(|i| format!("{}\n", i)).collect::<String>();
+    let new = old.replacen("2\n", "two\n", 1).replace("19\n", "");
+    assert_eq!(
+        unified_diff(&old, &new, "old", "new"),
+        "--- old
++++ new
+@@ -1,5 +1,5 @@
+ 1
+-2
++two
+ 3
+ 4
+ 5
+@@ -16,5 +16,4 @@
+ 16
+ 17
+ 18
+-19
+ 20
+"
+    );
+    // Insertions into an empty file and missing newlines at the end
+    assert_eq!(
+        unified_diff("", "a", "old", "new"),
+        "--- old\n+++ new\n@@ -0,0 +1,1 @@\n+a\n\\ No newline at end of file\n"
+    );
+}
+
+#[test]
+fn test_format_diff() {
+    use crate::formatting::{pretty_print, FormatLineFlow};
+
+    // Mirrors `format --diff`, which fails if the diff is not empty
+    let flags = crate::bazel_flags::load_packaged_bazel_flags("7.4.0");
+    let format_diff = |input: &str| {
+        let formatted = pretty_print(input, &flags, FormatLineFlow::Keep).unwrap();
+        unified_diff(input, &formatted, "a.bazelrc", "a.bazelrc")
+    };
+    // Files which need formatting
+    assert_eq!(
+        format_diff("# Jobs\nbuild   --jobs 4\ntest --keep_going\n"),
+        "--- a.bazelrc\n+++ a.bazelrc\n@@ -1,3 +1,3 @@\n # Jobs\n-build   --jobs 4\n+build --jobs=4\n test --keep_going\n"
+    );
+    // Files which are already formatted
+    assert_eq!(
+        format_diff("# Jobs\nbuild --jobs=4\ntest --keep_going\n"),
+        ""
+    );
+}
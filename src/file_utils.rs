@@ -24,6 +24,23 @@ pub fn get_workspace_path(path: &Path) -> Option<PathBuf> {
     find_file_in_parent_dirs(path, &ROOT_FILE_NAME)
 }
 
+// The files which define the external dependencies of a workspace
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct WorkspaceFiles {
+    // Bzlmod, i.e. `MODULE.bazel`
+    pub has_module_file: bool,
+    // The legacy `WORKSPACE` or `WORKSPACE.bazel`
+    pub has_workspace_file: bool,
+}
+
+pub fn get_workspace_files(workspace_path: &Path) -> WorkspaceFiles {
+    WorkspaceFiles {
+        has_module_file: workspace_path.join("MODULE.bazel").is_file(),
+        has_workspace_file: workspace_path.join("WORKSPACE").is_file()
+            || workspace_path.join("WORKSPACE.bazel").is_file(),
+    }
+}
+
 pub fn resolve_bazelrc_path(file_path: &Path, raw_path: &str) -> Option<PathBuf> {
     let mut path = raw_path.to_string();
     if path.contains("%workspace%") {
@@ -0,0 +1,371 @@
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+
+use crate::bazel_flags::load_packaged_bazel_flag_collection;
+use crate::bazel_flags_proto::{FlagCollection, FlagInfo};
+use crate::bazel_version::sort_bazel_versions;
+
+// The state of a flag in a specific Bazel version
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FlagState {
+    Available,
+    NoOp,
+    // The flag is only available under its old name. Contains the new name.
+    Renamed(String),
+    Missing,
+}
+
+// The states which break a flag when upgrading Bazel, i.e. all `FlagState`s except `Available`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FlagUpgradeState {
+    NoOp,
+    // Contains the new name
+    Renamed(String),
+    Missing,
+}
+
+// A change of a flag between two Bazel versions
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FlagChange {
+    // The first version in which the flag has its new state
+    pub version: String,
+    pub state: FlagUpgradeState,
+}
+
+// The ways in which a flag can change between two Bazel versions
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FlagDiffKind {
+    Added,
+    Removed,
+    // Contains the old name
+    Renamed(String),
+    Deprecated,
+    NoOp,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FlagDiff {
+    pub name: String,
+    pub kind: FlagDiffKind,
+}
+
+// The flags of all Bazel versions for which we have flag data.
+//
+// In contrast to `BazelFlags`, which only indexes the flags of a single Bazel version,
+// this keeps track of how flags evolved across Bazel versions.
+#[derive(Debug)]
+pub struct FlagHistory {
+    // All known versions, sorted from oldest to newest
+    pub versions: Vec<String>,
+    flags: Vec<FlagInfo>,
+    // Indices into `flags`, by name and by old name
+    flags_by_name: HashMap<String, Vec<usize>>,
+}
+
+impl FlagHistory {
+    pub fn from_collection(collection: FlagCollection) -> FlagHistory {
+        let mut versions = collection.all_bazel_versions;
+        sort_bazel_versions(&mut versions);
+        let mut flags_by_name = HashMap::<String, Vec<usize>>::new();
+        for (i, f) in collection.flag_infos.iter().enumerate() {
+            flags_by_name.entry(f.name.clone()).or_default().push(i);
+            if let Some(old_name) = &f.old_name {
+                flags_by_name.entry(old_name.clone()).or_default().push(i);
+            }
+        }
+        FlagHistory {
+            versions,
+            flags: collection.flag_infos,
+            flags_by_name,
+        }
+    }
+
+    pub fn get_state(&self, name: &str, version: &str) -> FlagState {
+        let mut state = FlagState::Missing;
+        for i in self.flags_by_name.get(name).into_iter().flatten() {
+            let flag = &self.flags[*i];
+            if !flag.bazel_versions.iter().any(|v| v == version) {
+                continue;
+            }
+            if flag.name != name {
+                state = FlagState::Renamed(flag.name.clone());
+            } else if flag.is_noop() {
+                return FlagState::NoOp;
+            } else {
+                return FlagState::Available;
+            }
+        }
+        state
+    }
+
+    // Determines how a flag changes when upgrading from `current_version` to `target_version`.
+    // Only reports flags which are removed, renamed or turned into no-ops in the target version.
+    // If the current version is unknown, all older versions are considered.
+    pub fn find_upgrade_change(
+        &self,
+        name: &str,
+        current_version: Option<&str>,
+        target_version: &str,
+    ) -> Option<FlagChange> {
+        let target_state = self.get_state(name, target_version);
+        let upgrade_state = match &target_state {
+            FlagState::Available => return None,
+            FlagState::NoOp => FlagUpgradeState::NoOp,
+            FlagState::Renamed(new_name) => FlagUpgradeState::Renamed(new_name.clone()),
+            FlagState::Missing => FlagUpgradeState::Missing,
+        };
+        // Walk backwards from the target version to find the first version with the new state
+        let mut first_version = None;
+        for version in self
+            .versions
+            .iter()
+            .rev()
+            .skip_while(|v| *v != target_version)
+        {
+            if self.get_state(name, version) != target_state {
+                return first_version.map(|v: &String| FlagChange {
+                    version: v.clone(),
+                    state: upgrade_state,
+                });
+            }
+            if Some(version.as_str()) == current_version {
+                // The flag already has this state in the current version
+                return None;
+            }
+            first_version = Some(version);
+        }
+        // The flag had this state in all known versions
+        None
+    }
+
+    // For a flag which is not available in `current_version`, finds the first newer version
+    // in which the flag is available
+    pub fn find_introducing_version(&self, name: &str, current_version: &str) -> Option<&str> {
+        if self.get_state(name, current_version) != FlagState::Missing {
+            return None;
+        }
+        self.versions
+            .iter()
+            .skip_while(|v| *v != current_version)
+            .skip(1)
+            .find(|v| self.get_state(name, v) != FlagState::Missing)
+            .map(String::as_str)
+    }
+
+    // The flag with exactly this name in the given version
+    fn get_flag(&self, name: &str, version: &str) -> Option<&FlagInfo> {
+        self.flags_by_name
+            .get(name)
+            .into_iter()
+            .flatten()
+            .map(|i| &self.flags[*i])
+            .find(|f| f.name == name && f.bazel_versions.iter().any(|v| v == version))
+    }
+
+    // Lists the flags which were added, removed, renamed, deprecated or turned into no-ops
+    // between two versions, sorted by kind and name
+    pub fn diff_versions(&self, from_version: &str, to_version: &str) -> Vec<FlagDiff> {
+        let mut names = self
+            .flags
+            .iter()
+            .map(|f| f.name.as_str())
+            .collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+        let mut result = Vec::new();
+        for name in names {
+            let kind = match (
+                self.get_flag(name, from_version),
+                self.get_flag(name, to_version),
+            ) {
+                (None, Some(to)) => match &to.old_name {
+                    Some(old_name) if self.get_flag(old_name, from_version).is_some() => {
+                        FlagDiffKind::Renamed(old_name.clone())
+                    }
+                    _ => FlagDiffKind::Added,
+                },
+                (Some(_), None) => {
+                    let is_renamed = self.flags.iter().any(|f| {
+                        f.old_name.as_deref() == Some(name)
+                            && f.bazel_versions.iter().any(|v| v == to_version)
+                    });
+                    if is_renamed {
+                        // Reported under the new name
+                        continue;
+                    }
+                    FlagDiffKind::Removed
+                }
+                (Some(from), Some(to)) if to.is_noop() && !from.is_noop() => FlagDiffKind::NoOp,
+                (Some(from), Some(to)) if to.is_deprecated() && !from.is_deprecated() => {
+                    FlagDiffKind::Deprecated
+                }
+                _ => continue,
+            };
+            result.push(FlagDiff {
+                name: name.to_string(),
+                kind,
+            });
+        }
+        result.sort_by_key(|d| {
+            let rank = match d.kind {
+                FlagDiffKind::Added => 0,
+                FlagDiffKind::Removed => 1,
+                FlagDiffKind::Renamed(_) => 2,
+                FlagDiffKind::Deprecated => 3,
+                FlagDiffKind::NoOp => 4,
+            };
+            (rank, d.name.clone())
+        });
+        result
+    }
+
+    // The ranges of consecutive versions in which a flag is available under the given name
+    pub fn get_version_ranges(&self, name: &str) -> Vec<(&str, &str)> {
+        let mut ranges = Vec::<(&str, &str)>::new();
+        let mut previous_available = false;
+        for version in &self.versions {
+            let available = matches!(
+                self.get_state(name, version),
+                FlagState::Available | FlagState::NoOp
+            );
+            match ranges.last_mut() {
+                Some(last) if available && previous_available => last.1 = version,
+                _ if available => ranges.push((version, version)),
+                _ => {}
+            }
+            previous_available = available;
+        }
+        ranges
+    }
+}
+
+pub static PACKAGED_FLAG_HISTORY: Lazy<FlagHistory> =
+    Lazy::new(|| FlagHistory::from_collection(load_packaged_bazel_flag_collection()));
+
+#[test]
+fn test_find_upgrade_change() {
+    let history = &PACKAGED_FLAG_HISTORY;
+    // Flags which remain available are not reported
+    assert_eq!(
+        history.find_upgrade_change("keep_going", Some("7.0.0"), "8.0.0"),
+        None
+    );
+    // `python3_path` was removed in Bazel 8
+    let removed = Some(FlagChange {
+        version: "8.0.0".to_string(),
+        state: FlagUpgradeState::Missing,
+    });
+    assert_eq!(
+        history.find_upgrade_change("python3_path", Some("7.0.0"), "8.0.0"),
+        removed
+    );
+    assert_eq!(
+        history.find_upgrade_change("python3_path", Some("7.0.0"), "9.0.0"),
+        removed
+    );
+    assert_eq!(
+        history.find_upgrade_change("python3_path", None, "9.0.0"),
+        removed
+    );
+    // No changes are reported for downgrades or if nothing changes compared to the current version
+    assert_eq!(
+        history.find_upgrade_change("python3_path", Some("9.0.0"), "7.0.0"),
+        None
+    );
+    assert_eq!(
+        history.find_upgrade_change("python3_path", Some("8.0.0"), "9.0.0"),
+        None
+    );
+    // Flags which never existed are not reported
+    assert_eq!(
+        history.find_upgrade_change("no_such_flag", Some("7.0.0"), "8.0.0"),
+        None
+    );
+}
+
+#[test]
+fn test_find_introducing_version() {
+    let flag = crate::bazel_flags::test_flag_info;
+    let history = FlagHistory::from_collection(FlagCollection {
+        flag_infos: vec![
+            flag("old_flag", &["6.5.0", "7.1.0"]),
+            flag("new_flag", &["7.1.0", "8.0.0"]),
+        ],
+        all_bazel_versions: ["8.0.0", "6.5.0", "7.1.0"].map(String::from).to_vec(),
+    });
+    assert_eq!(
+        history.find_introducing_version("new_flag", "6.5.0"),
+        Some("7.1.0")
+    );
+    assert_eq!(history.find_introducing_version("new_flag", "7.1.0"), None);
+    assert_eq!(history.find_introducing_version("old_flag", "7.1.0"), None);
+    assert_eq!(
+        history.find_introducing_version("no_such_flag", "6.5.0"),
+        None
+    );
+    // Unknown versions, e.g. when using the flags from a Bazel binary
+    assert_eq!(history.find_introducing_version("new_flag", "7.0.0"), None);
+}
+
+#[test]
+fn test_get_version_ranges() {
+    let flag = crate::bazel_flags::test_flag_info;
+    let history = FlagHistory::from_collection(FlagCollection {
+        flag_infos: vec![flag("flag", &["6.5.0", "7.1.0"]), flag("flag", &["8.1.0"])],
+        all_bazel_versions: ["6.5.0", "7.1.0", "8.0.0", "8.1.0"]
+            .map(String::from)
+            .to_vec(),
+    });
+    assert_eq!(
+        history.get_version_ranges("flag"),
+        vec![("6.5.0", "7.1.0"), ("8.1.0", "8.1.0")]
+    );
+    assert_eq!(history.get_version_ranges("no_such_flag"), vec![]);
+}
+
+#[test]
+fn test_diff_versions() {
+    let flag = crate::bazel_flags::test_flag_info;
+    let history = FlagHistory::from_collection(FlagCollection {
+        flag_infos: vec![
+            flag("unchanged", &["7.0.0", "8.0.0"]),
+            flag("added", &["8.0.0"]),
+            flag("removed", &["7.0.0"]),
+            flag("old_name", &["7.0.0"]),
+            FlagInfo {
+                old_name: Some("old_name".to_string()),
+                ..flag("new_name", &["8.0.0"])
+            },
+            flag("noop", &["7.0.0"]),
+            FlagInfo {
+                effect_tags: vec!["NO_OP".to_string()],
+                ..flag("noop", &["8.0.0"])
+            },
+            flag("deprecated", &["7.0.0"]),
+            FlagInfo {
+                metadata_tags: vec!["DEPRECATED".to_string()],
+                ..flag("deprecated", &["8.0.0"])
+            },
+        ],
+        all_bazel_versions: vec!["7.0.0".to_string(), "8.0.0".to_string()],
+    });
+    let diff = |name: &str, kind| FlagDiff {
+        name: name.to_string(),
+        kind,
+    };
+    assert_eq!(
+        history.diff_versions("7.0.0", "8.0.0"),
+        vec![
+            diff("added", FlagDiffKind::Added),
+            diff("removed", FlagDiffKind::Removed),
+            diff("new_name", FlagDiffKind::Renamed("old_name".to_string())),
+            diff("deprecated", FlagDiffKind::Deprecated),
+            diff("noop", FlagDiffKind::NoOp),
+        ]
+    );
+    // For downgrades, renamed flags are reported as added and removed
+    let downgrade = history.diff_versions("8.0.0", "7.0.0");
+    assert!(downgrade.contains(&diff("old_name", FlagDiffKind::Added)));
+    assert!(downgrade.contains(&diff("new_name", FlagDiffKind::Removed)));
+}
@@ -0,0 +1,216 @@
+use once_cell::sync::Lazy;
+use phf::phf_map;
+use regex::Regex;
+
+// The type of value expected by a flag.
+//
+// Bazel's flag dump does not include the value types of flags, yet.
+// The only type information it contains is whether a flag has a negative form, which
+// Bazel only offers for booleans and tri-states. For everything else, we maintain the
+// types of commonly used flags in `FLAG_VALUE_TYPES`. The values of all other flags
+// are not validated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlagValueType {
+    Boolean,
+    // A boolean which can also be `auto`
+    TriState,
+    // A flag with a negative form which is not known to be a `Boolean` or a `TriState`
+    BooleanOrTriState,
+    Integer,
+    // An integer or one of the keywords `auto`, `HOST_CPUS`, `HOST_RAM`,
+    // optionally followed by an operation, e.g. `HOST_CPUS*.5`
+    Resource,
+    // A duration like `1h30m` or `10s`. Plain integers are interpreted as seconds.
+    Duration,
+    // A single label
+    Label,
+    // A comma-separated list of labels
+    Labels,
+    // A file system path. Bazel expands a leading `~` to the user's home directory.
+    Path,
+    // One of the given values. Compared case-insensitively, like Bazel's `EnumConverter`
+    Enum(&'static [&'static str]),
+}
+
+const BOOLEAN_VALUES: [&str; 10] = ["true", "false", "yes", "no", "1", "0", "t", "f", "y", "n"];
+const COMPILATION_MODES: &[&str] = &["fastbuild", "dbg", "opt"];
+const YES_NO_AUTO: &[&str] = &["yes", "no", "auto"];
+
+// Value types for flags. Flags with a negative form are not necessarily booleans,
+// e.g. tri-state flags also accept `auto`. Hence, booleans are listed explicitly.
+// Keep in sync with the packaged flags, see `test_flag_value_types_exist`.
+pub static FLAG_VALUE_TYPES: phf::Map<&'static str, FlagValueType> = phf_map! {
+    // Booleans
+    "announce_rc" => FlagValueType::Boolean,
+    "build_runfile_links" => FlagValueType::Boolean,
+    "build_tests_only" => FlagValueType::Boolean,
+    "enable_bzlmod" => FlagValueType::Boolean,
+    "enable_workspace" => FlagValueType::Boolean,
+    "incompatible_strict_action_env" => FlagValueType::Boolean,
+    "keep_going" => FlagValueType::Boolean,
+    "remote_accept_cached" => FlagValueType::Boolean,
+    "remote_upload_local_results" => FlagValueType::Boolean,
+    "sandbox_debug" => FlagValueType::Boolean,
+    "show_timestamps" => FlagValueType::Boolean,
+    "verbose_failures" => FlagValueType::Boolean,
+    "watchfs" => FlagValueType::Boolean,
+    // Booleans which can also be `auto`
+    "build_python_zip" => FlagValueType::TriState,
+    "cache_test_results" => FlagValueType::TriState,
+    "enable_runfiles" => FlagValueType::TriState,
+    // Integers
+    "local_startup_timeout_secs" => FlagValueType::Integer,
+    "local_termination_grace_seconds" => FlagValueType::Integer,
+    "max_config_changes_to_show" => FlagValueType::Integer,
+    "max_idle_secs" => FlagValueType::Integer,
+    "remote_max_connections" => FlagValueType::Integer,
+    "remote_retries" => FlagValueType::Integer,
+    "show_result" => FlagValueType::Integer,
+    // Resources
+    "jobs" => FlagValueType::Resource,
+    "loading_phase_threads" => FlagValueType::Resource,
+    "local_cpu_resources" => FlagValueType::Resource,
+    "local_ram_resources" => FlagValueType::Resource,
+    "local_test_jobs" => FlagValueType::Resource,
+    // Durations
+    "bes_timeout" => FlagValueType::Duration,
+    "grpc_keepalive_time" => FlagValueType::Duration,
+    "grpc_keepalive_timeout" => FlagValueType::Duration,
+    "remote_retry_max_delay" => FlagValueType::Duration,
+    "remote_timeout" => FlagValueType::Duration,
+    // Labels
+    "crosstool_top" => FlagValueType::Label,
+    "host_crosstool_top" => FlagValueType::Label,
+    "host_platform" => FlagValueType::Label,
+    "extra_execution_platforms" => FlagValueType::Labels,
+    "extra_toolchains" => FlagValueType::Labels,
+    "platforms" => FlagValueType::Labels,
+    // Paths
+    "disk_cache" => FlagValueType::Path,
+    "distdir" => FlagValueType::Path,
+    "execution_log_binary_file" => FlagValueType::Path,
+    "execution_log_json_file" => FlagValueType::Path,
+    "repository_cache" => FlagValueType::Path,
+    // Enums
+    "bes_upload_mode" => FlagValueType::Enum(&["wait_for_upload_complete", "nowait_for_upload_complete", "fully_async"]),
+    "color" => FlagValueType::Enum(YES_NO_AUTO),
+    "compilation_mode" => FlagValueType::Enum(COMPILATION_MODES),
+    "curses" => FlagValueType::Enum(YES_NO_AUTO),
+    "experimental_convenience_symlinks" => FlagValueType::Enum(&["normal", "clean", "ignore", "log_only"]),
+    "host_compilation_mode" => FlagValueType::Enum(COMPILATION_MODES),
+    "lockfile_mode" => FlagValueType::Enum(&["off", "update", "refresh", "error"]),
+    "remote_download_outputs" => FlagValueType::Enum(&["all", "minimal", "toplevel"]),
+    "subcommands" => FlagValueType::Enum(&["true", "false", "yes", "no", "1", "0", "pretty_print"]),
+    "test_output" => FlagValueType::Enum(&["summary", "errors", "all", "streamed"]),
+    "test_summary" => FlagValueType::Enum(&["short", "terse", "detailed", "none", "testcase"]),
+};
+
+static RESOURCE_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(\d+|(auto|HOST_CPUS|HOST_RAM)([-*]\d*\.?\d+)?)$").unwrap());
+static DURATION_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(\d+|(\d+d)?(\d+h)?(\d+m)?(\d+s)?(\d+ms)?)$").unwrap());
+static LABEL_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^((@@?[\w.~+-]*)?//[^:]*(:[^:]+)?|:[^:]+)$").unwrap());
+
+// Parses a boolean value the same way Bazel's `BooleanConverter` does
+pub fn parse_boolean(value: &str) -> Option<bool> {
+    let index = BOOLEAN_VALUES
+        .iter()
+        .position(|v| v.eq_ignore_ascii_case(value))?;
+    // `BOOLEAN_VALUES` alternates between `true` and `false` values
+    Some(index % 2 == 0)
+}
+
+impl FlagValueType {
+    pub fn is_valid(&self, value: &str) -> bool {
+        match self {
+            FlagValueType::Boolean => parse_boolean(value).is_some(),
+            FlagValueType::TriState | FlagValueType::BooleanOrTriState => {
+                value.eq_ignore_ascii_case("auto") || FlagValueType::Boolean.is_valid(value)
+            }
+            FlagValueType::Integer => value.parse::<i64>().is_ok(),
+            FlagValueType::Resource => RESOURCE_REGEX.is_match(value),
+            FlagValueType::Duration => !value.is_empty() && DURATION_REGEX.is_match(value),
+            FlagValueType::Label => LABEL_REGEX.is_match(value),
+            FlagValueType::Labels => value.split(',').all(|l| LABEL_REGEX.is_match(l)),
+            FlagValueType::Path => true,
+            FlagValueType::Enum(values) => values.iter().any(|v| v.eq_ignore_ascii_case(value)),
+        }
+    }
+
+    // Describes the expected values, for use in diagnostics
+    pub fn describe(&self) -> String {
+        match self {
+            FlagValueType::Boolean => {
+                "a boolean (`true`, `false`, `yes`, `no`, `t`, `f`, `y`, `n`, `1` or `0`)"
+                    .to_string()
+            }
+            FlagValueType::TriState => format!("{} or `auto`", FlagValueType::Boolean.describe()),
+            FlagValueType::BooleanOrTriState => format!(
+                "{}, or `auto` for tri-state flags",
+                FlagValueType::Boolean.describe()
+            ),
+            FlagValueType::Integer => "an integer".to_string(),
+            FlagValueType::Resource => "an integer or `auto`, `HOST_CPUS`, `HOST_RAM`, optionally followed by an operation like `*.5`".to_string(),
+            FlagValueType::Duration => "a duration like `30s` or `1h30m`".to_string(),
+            FlagValueType::Label => "a label like `//my/package:target`".to_string(),
+            FlagValueType::Labels => {
+                "a comma-separated list of labels like `//my/package:target`".to_string()
+            }
+            FlagValueType::Path => "a path".to_string(),
+            FlagValueType::Enum(values) => format!(
+                "one of {}",
+                values
+                    .iter()
+                    .map(|v| format!("`{}`", v))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+#[test]
+fn test_value_validation() {
+    assert_eq!(parse_boolean("yes"), Some(true));
+    assert_eq!(parse_boolean("F"), Some(false));
+    assert_eq!(parse_boolean("maybe"), None);
+    assert!(FlagValueType::Boolean.is_valid("true"));
+    assert!(FlagValueType::Boolean.is_valid("No"));
+    assert!(!FlagValueType::Boolean.is_valid("maybe"));
+    assert!(!FlagValueType::Boolean.is_valid(""));
+    assert!(FlagValueType::TriState.is_valid("auto"));
+    assert!(FlagValueType::Integer.is_valid("-3"));
+    assert!(!FlagValueType::Integer.is_valid("lots"));
+    assert!(FlagValueType::Resource.is_valid("8"));
+    assert!(FlagValueType::Resource.is_valid("auto"));
+    assert!(FlagValueType::Resource.is_valid("HOST_CPUS*.5"));
+    assert!(FlagValueType::Resource.is_valid("HOST_RAM-1000"));
+    assert!(!FlagValueType::Resource.is_valid("lots"));
+    assert!(FlagValueType::Duration.is_valid("60"));
+    assert!(FlagValueType::Duration.is_valid("1h30m"));
+    assert!(FlagValueType::Duration.is_valid("500ms"));
+    assert!(!FlagValueType::Duration.is_valid(""));
+    assert!(!FlagValueType::Duration.is_valid("1 hour"));
+    assert!(FlagValueType::Labels.is_valid("//my/package:target"));
+    assert!(FlagValueType::Labels.is_valid("@platforms//os:linux,@@rules_cc~//cc:toolchain"));
+    assert!(!FlagValueType::Labels.is_valid("my_platform"));
+    assert!(FlagValueType::Label.is_valid("//my/package:target"));
+    assert!(!FlagValueType::Label.is_valid("//a:b,//c:d"));
+    assert!(FlagValueType::BooleanOrTriState.is_valid("auto"));
+    assert!(FlagValueType::BooleanOrTriState.is_valid("no"));
+    assert!(!FlagValueType::BooleanOrTriState.is_valid("maybe"));
+    assert!(FlagValueType::Enum(COMPILATION_MODES).is_valid("OPT"));
+    assert!(!FlagValueType::Enum(COMPILATION_MODES).is_valid("fast"));
+}
+
+#[test]
+fn test_flag_value_types_exist() {
+    // Catch typos and flags which were removed from all packaged Bazel versions
+    let history = &crate::flag_history::PACKAGED_FLAG_HISTORY;
+    let unknown = FLAG_VALUE_TYPES
+        .keys()
+        .filter(|name| history.get_version_ranges(name).is_empty())
+        .collect::<Vec<_>>();
+    assert_eq!(unknown, Vec::<&&str>::new());
+}
@@ -0,0 +1,275 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::json;
+
+use crate::rc_order::{flatten_imports, RcFile};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GraphNode {
+    // A bazelrc file. `missing` is set for imported files which don't exist.
+    File { path: PathBuf, missing: bool },
+    Config(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GraphEdge {
+    // Indices into `RcGraph::nodes`
+    pub from: usize,
+    pub to: usize,
+    // `import`, `try-import`, or the command of a `--config` reference
+    pub label: String,
+}
+
+// The graph of `import`s between bazelrc files and of `--config` references between configs
+#[derive(Debug, Default)]
+pub struct RcGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+impl RcGraph {
+    fn get_or_add_node(&mut self, node: GraphNode) -> usize {
+        // A file is the same node, no matter whether it exists
+        let existing = self.nodes.iter().position(|n| match (n, &node) {
+            (GraphNode::File { path: a, .. }, GraphNode::File { path: b, .. }) => a == b,
+            _ => *n == node,
+        });
+        existing.unwrap_or_else(|| {
+            self.nodes.push(node);
+            self.nodes.len() - 1
+        })
+    }
+
+    fn add_edge(&mut self, edge: GraphEdge) {
+        if !self.edges.contains(&edge) {
+            self.edges.push(edge);
+        }
+    }
+
+    pub fn from_rc_files(rc_files: &[RcFile]) -> RcGraph {
+        let mut graph = RcGraph::default();
+        for rc_file in rc_files {
+            let root_path = rc_file
+                .imported
+                .root_path
+                .clone()
+                .unwrap_or(rc_file.path.clone());
+            graph.get_or_add_node(GraphNode::File {
+                path: root_path.clone(),
+                missing: false,
+            });
+            for import in &rc_file.imported.imports {
+                let from = graph.get_or_add_node(GraphNode::File {
+                    path: import.from.clone(),
+                    missing: false,
+                });
+                let to = graph.get_or_add_node(GraphNode::File {
+                    path: import.path.clone(),
+                    missing: import.path != root_path
+                        && rc_file.imported.get_file(&import.path).is_none(),
+                });
+                let label = if import.optional {
+                    "try-import"
+                } else {
+                    "import"
+                };
+                graph.add_edge(GraphEdge {
+                    from,
+                    to,
+                    label: label.to_string(),
+                });
+            }
+            for rc_line in flatten_imports(&rc_file.lines, &rc_file.imported) {
+                let Some((command, _)) = &rc_line.line.command else {
+                    continue;
+                };
+                for flag in &rc_line.line.flags {
+                    let (Some(name), Some(value)) = (&flag.name, &flag.value) else {
+                        continue;
+                    };
+                    if name.0 != "--config" {
+                        continue;
+                    }
+                    // References from lines without config originate from the file itself
+                    let from = match &rc_line.line.config {
+                        Some(config) => graph.get_or_add_node(GraphNode::Config(config.0.clone())),
+                        None => graph.get_or_add_node(GraphNode::File {
+                            path: rc_line
+                                .file
+                                .map(|f| f.path.clone())
+                                .unwrap_or(root_path.clone()),
+                            missing: false,
+                        }),
+                    };
+                    let to = graph.get_or_add_node(GraphNode::Config(value.0.clone()));
+                    graph.add_edge(GraphEdge {
+                        from,
+                        to,
+                        label: command.clone(),
+                    });
+                }
+            }
+        }
+        graph
+    }
+
+    fn node_label(&self, node: &GraphNode, base_dir: &Path) -> String {
+        match node {
+            GraphNode::File { path, .. } => path
+                .strip_prefix(base_dir)
+                .unwrap_or(path)
+                .to_string_lossy()
+                .into_owned(),
+            GraphNode::Config(name) => format!("--config={}", name),
+        }
+    }
+
+    // Graphviz format. File paths are shown relative to `base_dir`.
+    pub fn to_dot(&self, base_dir: &Path) -> String {
+        let mut out = "digraph bazelrc {\n".to_string();
+        for (i, node) in self.nodes.iter().enumerate() {
+            let attributes = match node {
+                GraphNode::File { missing: true, .. } => "shape=box, style=dashed",
+                GraphNode::File { missing: false, .. } => "shape=box",
+                GraphNode::Config(_) => "shape=ellipse",
+            };
+            out += &format!(
+                "  n{} [label={:?}, {}];\n",
+                i,
+                self.node_label(node, base_dir),
+                attributes
+            );
+        }
+        for edge in &self.edges {
+            let style = if edge.label == "try-import" {
+                ", style=dashed"
+            } else {
+                ""
+            };
+            out += &format!(
+                "  n{} -> n{} [label={:?}{}];\n",
+                edge.from, edge.to, edge.label, style
+            );
+        }
+        out += "}\n";
+        out
+    }
+
+    // Mermaid flowchart, e.g. for embedding into Markdown documents
+    pub fn to_mermaid(&self, base_dir: &Path) -> String {
+        let mut out = "flowchart LR\n".to_string();
+        for (i, node) in self.nodes.iter().enumerate() {
+            // Mermaid doesn't support escaping `"` inside labels
+            let label = self.node_label(node, base_dir).replace('"', "#quot;");
+            out += &match node {
+                GraphNode::File { missing: true, .. } => {
+                    format!("  n{}[\"{}\"]:::missing\n", i, label)
+                }
+                GraphNode::File { missing: false, .. } => format!("  n{}[\"{}\"]\n", i, label),
+                GraphNode::Config(_) => format!("  n{}([\"{}\"])\n", i, label),
+            };
+        }
+        for edge in &self.edges {
+            let arrow = if edge.label == "try-import" {
+                "-.->"
+            } else {
+                "-->"
+            };
+            out += &format!("  n{} {}|{}| n{}\n", edge.from, arrow, edge.label, edge.to);
+        }
+        if self
+            .nodes
+            .iter()
+            .any(|n| matches!(n, GraphNode::File { missing: true, .. }))
+        {
+            out += "  classDef missing stroke-dasharray: 5 5\n";
+        }
+        out
+    }
+
+    pub fn to_json(&self, base_dir: &Path) -> String {
+        let nodes = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| match node {
+                GraphNode::File { path, missing } => json!({
+                    "id": format!("n{}", i),
+                    "type": "file",
+                    "label": self.node_label(node, base_dir),
+                    "path": path,
+                    "missing": missing,
+                }),
+                GraphNode::Config(name) => json!({
+                    "id": format!("n{}", i),
+                    "type": "config",
+                    "label": self.node_label(node, base_dir),
+                    "name": name,
+                }),
+            })
+            .collect::<Vec<_>>();
+        let edges = self
+            .edges
+            .iter()
+            .map(|e| {
+                json!({
+                    "from": format!("n{}", e.from),
+                    "to": format!("n{}", e.to),
+                    "label": e.label,
+                })
+            })
+            .collect::<Vec<_>>();
+        serde_json::to_string_pretty(&json!({ "nodes": nodes, "edges": edges })).unwrap()
+    }
+}
+
+#[test]
+fn test_rc_graph() {
+    let bazel_flags = crate::bazel_flags::load_packaged_bazel_flags("8.0.0");
+    let examples =
+        std::fs::canonicalize(Path::new(env!("CARGO_MANIFEST_DIR")).join("examples")).unwrap();
+    let rc_file = RcFile::parse(
+        &examples.join("graph.bazelrc"),
+        "import %workspace%/file2.bazelrc
+try-import %workspace%/no_such_file.bazelrc
+build --config=ci
+build:ci --config=remote
+build:remote --jobs=100
+",
+        &bazel_flags,
+    );
+    let graph = RcGraph::from_rc_files(&[rc_file]);
+    assert_eq!(
+        graph.to_dot(&examples),
+        r#"digraph bazelrc {
+  n0 [label="graph.bazelrc", shape=box];
+  n1 [label="file2.bazelrc", shape=box];
+  n2 [label="no_such_file.bazelrc", shape=box, style=dashed];
+  n3 [label="--config=ci", shape=ellipse];
+  n4 [label="--config=remote", shape=ellipse];
+  n0 -> n1 [label="import"];
+  n0 -> n2 [label="try-import", style=dashed];
+  n0 -> n3 [label="build"];
+  n3 -> n4 [label="build"];
+}
+"#
+    );
+    assert_eq!(
+        graph.to_mermaid(&examples),
+        r#"flowchart LR
+  n0["graph.bazelrc"]
+  n1["file2.bazelrc"]
+  n2["no_such_file.bazelrc"]:::missing
+  n3(["--config=ci"])
+  n4(["--config=remote"])
+  n0 -->|import| n1
+  n0 -.->|try-import| n2
+  n0 -->|build| n3
+  n3 -->|build| n4
+  classDef missing stroke-dasharray: 5 5
+"#
+    );
+    let json: serde_json::Value = serde_json::from_str(&graph.to_json(&examples)).unwrap();
+    assert_eq!(json["nodes"][2]["missing"], true);
+    assert_eq!(json["edges"][1]["label"], "try-import");
+}
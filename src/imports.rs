@@ -0,0 +1,144 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use ropey::Rope;
+
+use crate::{
+    bazel_flags::{combine_key_value_flags, BazelFlags},
+    file_utils::resolve_bazelrc_path,
+    parser::{parse_from_str, Line, ParserResult},
+    tokenizer::{Span, Spanned},
+};
+
+// A bazelrc file which was loaded through an `import` or `try-import`
+#[derive(Debug)]
+pub struct ImportedFile {
+    pub path: PathBuf,
+    pub rope: Rope,
+    pub lines: Vec<Line>,
+}
+
+// An `import` or `try-import` edge between two files
+#[derive(Debug)]
+pub struct Import {
+    // The importing file
+    pub from: PathBuf,
+    // The span of the file name inside the importing file
+    pub span: Span,
+    // The imported file
+    pub path: PathBuf,
+    // Set for `try-import`s
+    pub optional: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct ImportedFiles {
+    // The canonicalized path of the file we started loading from
+    pub root_path: Option<PathBuf>,
+    pub files: Vec<ImportedFile>,
+    // All resolvable imports, including imports of files which were already loaded
+    pub imports: Vec<Import>,
+    // Set if at least one `import` or `try-import` could not be resolved, or if a
+    // `try-import`ed file does not exist. Such a file might contain arbitrary settings.
+    // Missing `import`ed files are not included, as Bazel fails for them anyway.
+    pub has_missing_imports: bool,
+}
+
+impl ImportedFiles {
+    pub fn get_file(&self, path: &Path) -> Option<&ImportedFile> {
+        self.files.iter().find(|f| f.path == path)
+    }
+}
+
+// Get the path referenced by an `import` or `try-import` line
+pub fn get_import_path(line: &Line) -> Option<&Spanned<String>> {
+    let command = &line.command.as_ref()?.0;
+    if command != "import" && command != "try-import" {
+        return None;
+    }
+    if line.flags.len() != 1 || line.flags[0].name.is_some() {
+        return None;
+    }
+    line.flags[0].value.as_ref()
+}
+
+// Resolve symlinks and `..`, such that we can identify files which are imported through different paths
+pub fn canonicalize_path(path: PathBuf) -> PathBuf {
+    fs::canonicalize(&path).unwrap_or(path)
+}
+
+// Load all files imported by the given lines, transitively
+pub fn load_imported_files(
+    lines: &[Line],
+    file_path: Option<&Path>,
+    bazel_flags: &BazelFlags,
+) -> ImportedFiles {
+    let mut result = ImportedFiles {
+        root_path: file_path.map(|p| canonicalize_path(p.to_path_buf())),
+        ..Default::default()
+    };
+    let mut visited = HashSet::<PathBuf>::new();
+    if let Some(path) = &result.root_path {
+        visited.insert(path.clone());
+    }
+    let root_path = result.root_path.clone();
+    load_imported_files_into(
+        lines,
+        root_path.as_deref(),
+        bazel_flags,
+        &mut visited,
+        &mut result,
+    );
+    result
+}
+
+fn load_imported_files_into(
+    lines: &[Line],
+    file_path: Option<&Path>,
+    bazel_flags: &BazelFlags,
+    visited: &mut HashSet<PathBuf>,
+    result: &mut ImportedFiles,
+) {
+    for line in lines {
+        let Some(raw_path) = get_import_path(line) else {
+            continue;
+        };
+        let Some((from, path)) = file_path.and_then(|p| {
+            let resolved = resolve_bazelrc_path(p, &raw_path.0)?;
+            Some((p.to_path_buf(), canonicalize_path(resolved)))
+        }) else {
+            result.has_missing_imports = true;
+            continue;
+        };
+        let optional = line.command.as_ref().is_some_and(|c| c.0 == "try-import");
+        result.imports.push(Import {
+            from,
+            span: raw_path.1.clone(),
+            path: path.clone(),
+            optional,
+        });
+        if !visited.insert(path.clone()) {
+            // Already loaded
+            continue;
+        }
+        let Ok(contents) = fs::read_to_string(&path) else {
+            result.has_missing_imports |= optional;
+            continue;
+        };
+        let ParserResult {
+            tokens: _,
+            mut lines,
+            errors: _,
+        } = parse_from_str(&contents);
+        combine_key_value_flags(&mut lines, bazel_flags);
+        load_imported_files_into(&lines, Some(&path), bazel_flags, visited, result);
+        result.files.push(ImportedFile {
+            path,
+            rope: Rope::from_str(&contents),
+            lines,
+        });
+    }
+}
@@ -1,12 +1,23 @@
-use crate::bazel_flags::{combine_key_value_flags, BazelFlags, COMMAND_DOCS};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::bazel_flags::{
+    combine_key_value_flags, load_packaged_bazel_flags, BazelFlags, COMMAND_DOCS,
+};
+use crate::bazel_version::{find_closest_version, AVAILABLE_BAZEL_VERSIONS};
+use crate::code_actions::get_code_actions;
 use crate::completion::get_completion_items;
 use crate::definition::get_definitions;
-use crate::diagnostic::{diagnostics_from_parser, diagnostics_from_rcconfig};
-use crate::file_utils::resolve_bazelrc_path;
+use crate::diagnostic::{
+    diagnostics_from_parser, diagnostics_from_rcconfig, diagnostics_from_string,
+    merge_diagnostics_for_versions,
+};
+use crate::file_utils::{get_workspace_path, resolve_bazelrc_path};
 use crate::formatting::{get_text_edits_for_lines, FormatLineFlow};
 use crate::line_index::{IndexEntry, IndexEntryKind, IndexedLines};
 use crate::lsp_utils::{decode_lsp_pos, encode_lsp_range, LspPositionEncoding};
 use crate::parser::{parse_from_str, Line, ParserResult};
+use crate::rc_order::{get_default_rc_paths, RcChain};
 use crate::semantic_token::{
     convert_to_lsp_tokens, semantic_tokens_from_lines, RCSemanticToken, LEGEND_TYPE,
 };
@@ -36,6 +47,12 @@ pub struct AnalyzedDocument {
 pub struct Settings {
     #[serde(default)]
     pub format_lines: FormatLineFlow,
+    // The Bazel version to check upgrade-readiness against
+    #[serde(default)]
+    pub target_bazel_version: Option<String>,
+    // Further Bazel versions which the bazelrc files should be compatible with
+    #[serde(default)]
+    pub additional_bazel_versions: Vec<String>,
 }
 
 #[derive(Debug)]
@@ -43,13 +60,28 @@ pub struct Backend {
     pub client: Client,
     pub document_map: DashMap<String, AnalyzedDocument>,
     pub bazel_flags: BazelFlags,
+    // The flags for `Settings::additional_bazel_versions`
+    pub additional_bazel_flags: std::sync::RwLock<Vec<BazelFlags>>,
     pub position_encoding: std::sync::RwLock<LspPositionEncoding>,
     pub settings: std::sync::RwLock<Settings>,
     // An optional message which should be displayed to the user on startup
     pub startup_warning: Option<String>,
+    // The default bazelrc files of each workspace. Invalidated when one of their files is saved.
+    pub rc_chains: DashMap<Option<PathBuf>, Arc<RcChain>>,
 }
 
 impl Backend {
+    fn get_rc_chain(&self, file_path: Option<&Path>) -> Arc<RcChain> {
+        let workspace_path = file_path.and_then(get_workspace_path);
+        self.rc_chains
+            .entry(workspace_path.clone())
+            .or_insert_with(|| {
+                let rc_paths = get_default_rc_paths(workspace_path.as_deref());
+                Arc::new(RcChain::load(&rc_paths, &self.bazel_flags))
+            })
+            .clone()
+    }
+
     async fn on_change(&self, params: TextDocumentItem) {
         let rope = ropey::Rope::from_str(&params.text);
         let src = rope.to_string();
@@ -67,15 +99,47 @@ impl Backend {
         let indexed_lines = IndexedLines::from_lines(lines);
 
         let position_encoding = *self.position_encoding.read().unwrap();
-        let mut diagnostics: Vec<Diagnostic> = Vec::<Diagnostic>::new();
-        diagnostics.extend(diagnostics_from_parser(&rope, &errors, position_encoding));
-        diagnostics.extend(diagnostics_from_rcconfig(
-            &rope,
-            &indexed_lines.lines,
-            &self.bazel_flags,
-            file_path,
-            position_encoding,
-        ));
+        let rc_chain = self.get_rc_chain(file_path);
+        let target_bazel_version = self
+            .settings
+            .read()
+            .unwrap()
+            .target_bazel_version
+            .clone()
+            .filter(|v| !v.is_empty());
+        let diagnostics = {
+            let additional_bazel_flags = self.additional_bazel_flags.read().unwrap();
+            if additional_bazel_flags.is_empty() {
+                let mut diagnostics: Vec<Diagnostic> = Vec::<Diagnostic>::new();
+                diagnostics.extend(diagnostics_from_parser(&rope, &errors, position_encoding));
+                diagnostics.extend(diagnostics_from_rcconfig(
+                    &rope,
+                    &indexed_lines.lines,
+                    &self.bazel_flags,
+                    file_path,
+                    &rc_chain,
+                    target_bazel_version.as_deref(),
+                    position_encoding,
+                ));
+                diagnostics
+            } else {
+                // Validate against all Bazel versions. This requires re-parsing, as flags are
+                // combined with their values based on the version-specific flag metadata.
+                let all_bazel_flags = std::iter::once(&self.bazel_flags)
+                    .chain(additional_bazel_flags.iter())
+                    .collect::<Vec<_>>();
+                merge_diagnostics_for_versions(&all_bazel_flags, |bazel_flags| {
+                    diagnostics_from_string(
+                        &src,
+                        bazel_flags,
+                        file_path,
+                        &rc_chain,
+                        target_bazel_version.as_deref(),
+                        position_encoding,
+                    )
+                })
+            }
+        };
 
         self.document_map.insert(
             params.uri.to_string(),
@@ -127,8 +191,14 @@ impl LanguageServer for Backend {
             }),
             offset_encoding: None,
             capabilities: ServerCapabilities {
-                text_document_sync: Some(TextDocumentSyncCapability::Kind(
-                    TextDocumentSyncKind::FULL,
+                text_document_sync: Some(TextDocumentSyncCapability::Options(
+                    TextDocumentSyncOptions {
+                        open_close: Some(true),
+                        change: Some(TextDocumentSyncKind::FULL),
+                        // Needed to invalidate the cached `rc_chains`
+                        save: Some(TextDocumentSyncSaveOptions::Supported(true)),
+                        ..Default::default()
+                    },
                 )),
                 semantic_tokens_provider: Some(
                     SemanticTokensServerCapabilities::SemanticTokensRegistrationOptions(
@@ -167,6 +237,12 @@ impl LanguageServer for Backend {
                     work_done_progress_options: Default::default(),
                 }),
                 definition_provider: Some(OneOf::Left(true)),
+                code_action_provider: Some(CodeActionProviderCapability::Options(
+                    CodeActionOptions {
+                        code_action_kinds: Some(vec![CodeActionKind::QUICKFIX]),
+                        ..Default::default()
+                    },
+                )),
                 ..ServerCapabilities::default()
             },
         })
@@ -197,7 +273,18 @@ impl LanguageServer for Backend {
             return;
         };
         match serde_json::from_value::<Settings>(bazelrc_settings) {
-            Ok(new_settings) => *self.settings.write().unwrap() = new_settings,
+            Ok(new_settings) => {
+                *self.additional_bazel_flags.write().unwrap() = new_settings
+                    .additional_bazel_versions
+                    .iter()
+                    .map(|v| {
+                        let bazel_version =
+                            find_closest_version(AVAILABLE_BAZEL_VERSIONS.as_slice(), v).0;
+                        load_packaged_bazel_flags(&bazel_version)
+                    })
+                    .collect();
+                *self.settings.write().unwrap() = new_settings;
+            }
             Err(err) => {
                 self.client
                     .show_message(MessageType::ERROR, format!("Invalid settings: {}", err))
@@ -227,6 +314,14 @@ impl LanguageServer for Backend {
         .await
     }
 
+    async fn did_save(&self, params: DidSaveTextDocumentParams) {
+        let Ok(path) = params.text_document.uri.to_file_path() else {
+            return;
+        };
+        self.rc_chains
+            .retain(|_, rc_chain| !rc_chain.contains_file(&path));
+    }
+
     async fn did_close(&self, params: DidCloseTextDocumentParams) {
         self.document_map
             .remove(&params.text_document.uri.to_string());
@@ -342,7 +437,8 @@ impl LanguageServer for Backend {
                     let line = &doc.indexed_lines.lines[*line_nr];
                     let flag_name = &line.flags.get(*flag_nr)?.name.as_ref()?.0;
                     let (_, flag_info) = self.bazel_flags.get_by_invocation(flag_name)?;
-                    let content = flag_info.get_documentation_markdown();
+                    let command = line.command.as_ref().map(|c| c.0.as_str());
+                    let content = self.bazel_flags.get_hover_markdown(flag_info, command);
                     let contents = HoverContents::Scalar(MarkedString::String(content));
                     Some(Hover {
                         contents,
@@ -353,6 +449,14 @@ impl LanguageServer for Backend {
         }())
     }
 
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        // The fixes are attached to the diagnostics sent back to us by the client
+        Ok(Some(get_code_actions(
+            &params.text_document.uri,
+            &params.context.diagnostics,
+        )))
+    }
+
     async fn formatting(&self, params: DocumentFormattingParams) -> Result<Option<Vec<TextEdit>>> {
         // Find the right document
         let position_encoding = *self.position_encoding.read().unwrap();
@@ -1,14 +1,26 @@
 pub mod bazel_flags;
 pub mod bazel_flags_proto;
 pub mod bazel_version;
+pub mod code_actions;
 pub mod completion;
+pub mod configs;
 pub mod definition;
 pub mod diagnostic;
+pub mod diff;
 pub mod file_utils;
+pub mod flag_history;
+pub mod flag_types;
 pub mod formatting;
+pub mod graph;
+pub mod imports;
 pub mod language_server;
 pub mod line_index;
+pub mod lint_output;
 pub mod lsp_utils;
+pub mod migrate;
 pub mod parser;
+pub mod rc_order;
 pub mod semantic_token;
+pub mod suggestions;
+pub mod terminal;
 pub mod tokenizer;
@@ -0,0 +1,494 @@
+use std::{fs, path::PathBuf};
+
+use serde_json::json;
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Position, Range};
+
+use crate::terminal::{Painter, BLUE, BOLD, CYAN, RED, YELLOW};
+
+// The diagnostics reported for one linted file
+pub struct LintedFile {
+    // The path as shown to the user, e.g. `<stdin>`
+    pub path: String,
+    // The absolute path, used to match the locations referenced by diagnostics
+    pub absolute_path: Option<PathBuf>,
+    pub source: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+fn severity_name(diagnostic: &Diagnostic) -> &'static str {
+    match diagnostic.severity {
+        Some(DiagnosticSeverity::WARNING) => "warning",
+        Some(DiagnosticSeverity::INFORMATION) => "info",
+        Some(DiagnosticSeverity::HINT) => "hint",
+        _ => "error",
+    }
+}
+
+// The rule which reported a diagnostic, e.g. `unknown-flag`
+fn rule_id(diagnostic: &Diagnostic) -> Option<String> {
+    match diagnostic.code.as_ref()? {
+        NumberOrString::Number(n) => Some(n.to_string()),
+        NumberOrString::String(s) => Some(s.clone()),
+    }
+}
+
+// LSP positions are 0-based, while all our output formats use 1-based lines and columns
+fn one_based(pos: &Position) -> (u32, u32) {
+    (pos.line + 1, pos.character + 1)
+}
+
+pub fn format_json(files: &[LintedFile]) -> String {
+    let entries = files
+        .iter()
+        .flat_map(|f| {
+            f.diagnostics.iter().map(|d| {
+                let (start_line, start_column) = one_based(&d.range.start);
+                let (end_line, end_column) = one_based(&d.range.end);
+                json!({
+                    "file": f.path,
+                    "severity": severity_name(d),
+                    "rule": rule_id(d),
+                    "message": d.message,
+                    "range": {
+                        "start": { "line": start_line, "column": start_column },
+                        "end": { "line": end_line, "column": end_column },
+                    },
+                })
+            })
+        })
+        .collect::<Vec<_>>();
+    serde_json::to_string_pretty(&entries).unwrap()
+}
+
+// See https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+pub fn format_sarif(files: &[LintedFile]) -> String {
+    let results = files
+        .iter()
+        .flat_map(|f| {
+            f.diagnostics.iter().map(|d| {
+                let level = match d.severity {
+                    Some(DiagnosticSeverity::WARNING) => "warning",
+                    Some(DiagnosticSeverity::INFORMATION) | Some(DiagnosticSeverity::HINT) => {
+                        "note"
+                    }
+                    _ => "error",
+                };
+                let (start_line, start_column) = one_based(&d.range.start);
+                let (end_line, end_column) = one_based(&d.range.end);
+                let mut result = json!({
+                    "level": level,
+                    "message": { "text": d.message },
+                    "locations": [{
+                        "physicalLocation": {
+                            "artifactLocation": { "uri": f.path },
+                            "region": {
+                                "startLine": start_line,
+                                "startColumn": start_column,
+                                "endLine": end_line,
+                                "endColumn": end_column,
+                            },
+                        },
+                    }],
+                });
+                if let Some(rule_id) = rule_id(d) {
+                    result["ruleId"] = rule_id.into();
+                }
+                result
+            })
+        })
+        .collect::<Vec<_>>();
+    // All rules which reported at least one result
+    let mut rule_ids = files
+        .iter()
+        .flat_map(|f| f.diagnostics.iter().filter_map(rule_id))
+        .collect::<Vec<_>>();
+    rule_ids.sort();
+    rule_ids.dedup();
+    let rules = rule_ids
+        .iter()
+        .map(|id| json!({ "id": id }))
+        .collect::<Vec<_>>();
+    let sarif = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "bazelrc-lsp",
+                    "informationUri": "https://github.com/salesforce-misc/bazelrc-lsp",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                },
+            },
+            "results": results,
+        }],
+    });
+    serde_json::to_string_pretty(&sarif).unwrap()
+}
+
+fn escape_github_data(str: &str) -> String {
+    str.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_github_property(str: &str) -> String {
+    escape_github_data(str)
+        .replace(':', "%3A")
+        .replace(',', "%2C")
+}
+
+// Workflow commands which GitHub Actions shows as annotations on pull requests.
+// See https://docs.github.com/en/actions/writing-workflows/choosing-what-your-workflow-does/workflow-commands-for-github-actions
+pub fn format_github(files: &[LintedFile]) -> String {
+    let mut out = String::new();
+    for f in files {
+        for d in &f.diagnostics {
+            let command = match d.severity {
+                Some(DiagnosticSeverity::WARNING) => "warning",
+                Some(DiagnosticSeverity::INFORMATION) | Some(DiagnosticSeverity::HINT) => "notice",
+                _ => "error",
+            };
+            let (start_line, start_column) = one_based(&d.range.start);
+            let (end_line, end_column) = one_based(&d.range.end);
+            let title = rule_id(d)
+                .map(|id| format!(",title={}", escape_github_property(&id)))
+                .unwrap_or_default();
+            out += &format!(
+                "::{} file={},line={},col={},endLine={},endColumn={}{}::{}\n",
+                command,
+                escape_github_property(&f.path),
+                start_line,
+                start_column,
+                end_line,
+                end_column,
+                title,
+                escape_github_data(&d.message)
+            );
+        }
+    }
+    out
+}
+
+fn escape_xml(str: &str) -> String {
+    str.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+        // Parsers would normalize a literal newline inside an attribute to a space
+        .replace('\n', "&#10;")
+}
+
+// The Checkstyle XML format, understood by many CI systems and code review tools
+pub fn format_checkstyle(files: &[LintedFile]) -> String {
+    let mut out = String::new();
+    out += "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n";
+    out += "<checkstyle version=\"4.3\">\n";
+    for f in files {
+        out += &format!("  <file name=\"{}\">\n", escape_xml(&f.path));
+        for d in &f.diagnostics {
+            // Checkstyle only knows about `error`, `warning` and `info`
+            let severity = match d.severity {
+                Some(DiagnosticSeverity::WARNING) => "warning",
+                Some(DiagnosticSeverity::INFORMATION) | Some(DiagnosticSeverity::HINT) => "info",
+                _ => "error",
+            };
+            let (line, column) = one_based(&d.range.start);
+            let source = match rule_id(d) {
+                Some(id) => format!("bazelrc-lsp.{}", id),
+                None => "bazelrc-lsp".to_string(),
+            };
+            out += &format!(
+                "    <error line=\"{}\" column=\"{}\" severity=\"{}\" message=\"{}\" source=\"{}\"/>\n",
+                line,
+                column,
+                severity,
+                escape_xml(&d.message),
+                escape_xml(&source)
+            );
+        }
+        out += "  </file>\n";
+    }
+    out += "</checkstyle>\n";
+    out
+}
+
+// Renders the source line of `range` and underlines the range with `marker`
+fn format_snippet(
+    painter: &Painter,
+    path: &str,
+    source: Option<&str>,
+    range: &Range,
+    marker: char,
+    style: &str,
+) -> String {
+    let (line_nr, column) = one_based(&range.start);
+    let gutter = " ".repeat(line_nr.to_string().len());
+    let mut out = format!(
+        "{}{} {}:{}:{}\n",
+        gutter,
+        painter.paint(BLUE, "-->"),
+        path,
+        line_nr,
+        column
+    );
+    let Some(line) = source.and_then(|s| s.lines().nth(range.start.line as usize)) else {
+        return out;
+    };
+    let bar = painter.paint(BLUE, "|");
+    let start = range.start.character as usize;
+    let end = if range.end.line == range.start.line {
+        range.end.character as usize
+    } else {
+        // Multi-line ranges are underlined until the end of the first line
+        line.chars().count()
+    };
+    // Keep tabs, so that the underline stays aligned with the source line
+    let indent = line
+        .chars()
+        .take(start)
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect::<String>();
+    let underline = marker.to_string().repeat(end.saturating_sub(start).max(1));
+    out += &format!("{} {}\n", gutter, bar);
+    out += &format!(
+        "{} {} {}\n",
+        painter.paint(BLUE, &line_nr.to_string()),
+        bar,
+        line
+    );
+    out += &format!(
+        "{} {} {}{}\n",
+        gutter,
+        bar,
+        indent,
+        painter.paint(style, &underline)
+    );
+    out
+}
+
+// Human-readable output, including the source lines of all reported issues
+pub fn format_text(file: &LintedFile, colored: bool) -> String {
+    let painter = Painter { colored };
+    let mut out = String::new();
+    for d in &file.diagnostics {
+        let style = match d.severity {
+            Some(DiagnosticSeverity::WARNING) => YELLOW,
+            Some(DiagnosticSeverity::INFORMATION) | Some(DiagnosticSeverity::HINT) => CYAN,
+            _ => RED,
+        };
+        let code = rule_id(d).map(|id| format!("[{}]", id)).unwrap_or_default();
+        out += &format!(
+            "{}{}\n",
+            painter.paint(style, &format!("{}{}:", severity_name(d), code)),
+            painter.paint(BOLD, &format!(" {}", d.message))
+        );
+        out += &format_snippet(
+            &painter,
+            &file.path,
+            Some(&file.source),
+            &d.range,
+            '^',
+            style,
+        );
+        for related in d.related_information.iter().flatten() {
+            out += &format!(
+                "{}{}\n",
+                painter.paint(CYAN, "note:"),
+                painter.paint(BOLD, &format!(" {}", related.message))
+            );
+            let related_path = related.location.uri.to_file_path().ok();
+            if related_path.is_some() && related_path == file.absolute_path {
+                out += &format_snippet(
+                    &painter,
+                    &file.path,
+                    Some(&file.source),
+                    &related.location.range,
+                    '-',
+                    CYAN,
+                );
+            } else {
+                // The location is in a different file, e.g. an imported file
+                let related_source = related_path
+                    .as_ref()
+                    .and_then(|p| fs::read_to_string(p).ok());
+                let display_path = related_path
+                    .map(|p| p.to_string_lossy().into_owned())
+                    .unwrap_or(related.location.uri.to_string());
+                out += &format_snippet(
+                    &painter,
+                    &display_path,
+                    related_source.as_deref(),
+                    &related.location.range,
+                    '-',
+                    CYAN,
+                );
+            }
+        }
+        out += "\n";
+    }
+    out
+}
+
+#[cfg(test)]
+fn test_files() -> Vec<LintedFile> {
+    use crate::diagnostic::diagnostics_from_string;
+    use crate::lsp_utils::LspPositionEncoding;
+    use crate::rc_order::RcChain;
+
+    let bazel_flags = crate::bazel_flags::load_packaged_bazel_flags("8.0.0");
+    let source = "build --jobs=4\nbuild --unknown_flag\ncommon --legacy_whole_archive\n";
+    let diagnostics = diagnostics_from_string(
+        source,
+        &bazel_flags,
+        None,
+        &RcChain::default(),
+        None,
+        LspPositionEncoding::UTF32,
+    );
+    vec![LintedFile {
+        path: "dir/a,b.bazelrc".to_string(),
+        absolute_path: None,
+        source: source.to_string(),
+        diagnostics,
+    }]
+}
+
+#[test]
+fn test_format_text() {
+    use tower_lsp::lsp_types::{DiagnosticRelatedInformation, Location, Url};
+    assert_eq!(
+        format_text(&test_files()[0], false),
+        "error[unknown-flag]: Unknown flag \"--unknown_flag\"
+ --> dir/a,b.bazelrc:2:7
+  |
+2 | build --unknown_flag
+  |       ^^^^^^^^^^^^^^
+
+warning[deprecated-flag]: The flag \"--legacy_whole_archive\" is deprecated.
+ --> dir/a,b.bazelrc:3:8
+  |
+3 | common --legacy_whole_archive
+  |        ^^^^^^^^^^^^^^^^^^^^^^
+
+"
+    );
+
+    // Related locations, in the same file and in other files
+    let absolute_path = PathBuf::from("/workspace/.bazelrc");
+    let file = LintedFile {
+        path: ".bazelrc".to_string(),
+        absolute_path: Some(absolute_path.clone()),
+        source: "build --keep_going\n\tbuild --nokeep_going\n".to_string(),
+        diagnostics: vec![Diagnostic {
+            range: Range::new(Position::new(0, 6), Position::new(0, 18)),
+            severity: Some(DiagnosticSeverity::HINT),
+            message: "\"--keep_going\" is overridden".to_string(),
+            related_information: Some(vec![
+                DiagnosticRelatedInformation {
+                    location: Location::new(
+                        Url::from_file_path(&absolute_path).unwrap(),
+                        Range::new(Position::new(1, 7), Position::new(1, 21)),
+                    ),
+                    message: "Overridden here".to_string(),
+                },
+                DiagnosticRelatedInformation {
+                    location: Location::new(
+                        Url::from_file_path("/no/such/file.bazelrc").unwrap(),
+                        Range::new(Position::new(11, 0), Position::new(11, 5)),
+                    ),
+                    message: "And here".to_string(),
+                },
+            ]),
+            ..Default::default()
+        }],
+    };
+    assert_eq!(
+        format_text(&file, false),
+        "hint: \"--keep_going\" is overridden
+ --> .bazelrc:1:7
+  |
+1 | build --keep_going
+  |       ^^^^^^^^^^^^
+note: Overridden here
+ --> .bazelrc:2:8
+  |
+2 | \tbuild --nokeep_going
+  | \t      --------------
+note: And here
+  --> /no/such/file.bazelrc:12:1
+
+"
+    );
+    // Colors are only added on request
+    assert!(!format_text(&file, false).contains('\x1b'));
+    assert!(format_text(&file, true).contains(CYAN));
+}
+
+#[test]
+fn test_format_json() {
+    let parsed: serde_json::Value = serde_json::from_str(&format_json(&test_files())).unwrap();
+    assert_eq!(
+        parsed[0],
+        json!({
+            "file": "dir/a,b.bazelrc",
+            "severity": "error",
+            "rule": "unknown-flag",
+            "message": "Unknown flag \"--unknown_flag\"",
+            "range": {
+                "start": { "line": 2, "column": 7 },
+                "end": { "line": 2, "column": 21 },
+            },
+        })
+    );
+    assert_eq!(parsed[1]["rule"], "deprecated-flag");
+}
+
+#[test]
+fn test_format_sarif() {
+    let parsed: serde_json::Value = serde_json::from_str(&format_sarif(&test_files())).unwrap();
+    let run = &parsed["runs"][0];
+    assert_eq!(
+        run["tool"]["driver"]["rules"],
+        json!([{ "id": "deprecated-flag" }, { "id": "unknown-flag" }])
+    );
+    let result = &run["results"][0];
+    assert_eq!(result["level"], "error");
+    assert_eq!(result["ruleId"], "unknown-flag");
+    let location = &result["locations"][0]["physicalLocation"];
+    assert_eq!(location["artifactLocation"]["uri"], "dir/a,b.bazelrc");
+    assert_eq!(location["region"]["startLine"], 2);
+    assert_eq!(location["region"]["endColumn"], 21);
+    assert_eq!(run["results"][1]["level"], "warning");
+}
+
+#[test]
+fn test_format_github() {
+    assert_eq!(
+        format_github(&test_files()),
+        "::error file=dir/a%2Cb.bazelrc,line=2,col=7,endLine=2,endColumn=21,title=unknown-flag::Unknown flag \"--unknown_flag\"
+::warning file=dir/a%2Cb.bazelrc,line=3,col=8,endLine=3,endColumn=30,title=deprecated-flag::The flag \"--legacy_whole_archive\" is deprecated.
+"
+    );
+    assert_eq!(escape_github_data("50%\nmore"), "50%25%0Amore");
+}
+
+#[test]
+fn test_format_checkstyle() {
+    assert_eq!(
+        format_checkstyle(&test_files()),
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<checkstyle version="4.3">
+  <file name="dir/a,b.bazelrc">
+    <error line="2" column="7" severity="error" message="Unknown flag &quot;--unknown_flag&quot;" source="bazelrc-lsp.unknown-flag"/>
+    <error line="3" column="8" severity="warning" message="The flag &quot;--legacy_whole_archive&quot; is deprecated." source="bazelrc-lsp.deprecated-flag"/>
+  </file>
+</checkstyle>
+"#
+    );
+    assert_eq!(
+        escape_xml("<a & 'b'>\n"),
+        "&lt;a &amp; &apos;b&apos;&gt;&#10;"
+    );
+}
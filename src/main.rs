@@ -1,6 +1,7 @@
-use std::io::Read;
+use std::collections::HashMap;
+use std::io::{IsTerminal, Read};
 use std::ops::Deref;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::{env, fs, io, process};
 
 use bazelrc_lsp::bazel_flags::{
@@ -9,11 +10,28 @@ use bazelrc_lsp::bazel_flags::{
 use bazelrc_lsp::bazel_version::{
     determine_bazelisk_version, find_closest_version, AVAILABLE_BAZEL_VERSIONS,
 };
-use bazelrc_lsp::diagnostic::diagnostics_from_string;
+use bazelrc_lsp::code_actions::apply_preferred_fixes;
+use bazelrc_lsp::configs::{
+    collect_configs, format_json as format_configs_json,
+    format_markdown as format_configs_markdown, format_table as format_configs_table,
+};
+use bazelrc_lsp::diagnostic::{diagnostics_from_string, merge_diagnostics_for_versions};
+use bazelrc_lsp::diff::unified_diff;
+use bazelrc_lsp::file_utils::get_workspace_path;
+use bazelrc_lsp::flag_history::{FlagDiffKind, PACKAGED_FLAG_HISTORY};
 use bazelrc_lsp::formatting::{pretty_print, FormatLineFlow};
+use bazelrc_lsp::graph::RcGraph;
 use bazelrc_lsp::language_server::{Backend, Settings};
+use bazelrc_lsp::lint_output::{
+    format_checkstyle, format_github, format_json, format_sarif, format_text, LintedFile,
+};
 use bazelrc_lsp::lsp_utils::LspPositionEncoding;
-use clap::{CommandFactory, Parser, Subcommand};
+use bazelrc_lsp::migrate::{migrate_bazelrc, MigrationAction};
+use bazelrc_lsp::rc_order::{expand_options, get_default_rc_paths, RcChain, RcFile};
+use bazelrc_lsp::suggestions::{find_similar, format_suggestions};
+use bazelrc_lsp::terminal::{render_markdown, Painter, BOLD};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use tower_lsp::lsp_types::DiagnosticSeverity;
 use tower_lsp::{LspService, Server};
 use walkdir::WalkDir;
 
@@ -70,10 +88,36 @@ enum Commands {
     /// If no arguments are specified, format the bazelrc contents
     /// from stdin and write the result to stdout.
     /// If <file>s are given, reformat the files. If -i is specified,
-    /// the files are edited in-place. Otherwise, the result is written to the stdout.
+    /// the files are edited in-place. If --diff is specified, the changes are
+    /// written to stdout as a unified diff. Otherwise, the result is written to the stdout.
     Format(FormatArgs),
     /// Check your bazelrc files for mistakes
     Lint(LintArgs),
+    /// Print the options Bazel applies for a command, similar to `--announce_rc`
+    ///
+    /// Reads the bazelrc files Bazel reads by default, including their imports,
+    /// and expands all `--config`s. Each option is annotated with its origin.
+    Expand(ExpandArgs),
+    /// Show the documentation of a flag
+    Flag(FlagArgs),
+    /// Upgrade bazelrc files to a newer Bazel version
+    ///
+    /// Replaces renamed flags by their new names, drops flags which became no-ops
+    /// and comments out removed flags. If no files are given, the bazelrc contents
+    /// are read from stdin. If -i is specified, the <file>s are edited in-place.
+    /// Otherwise, the result is written to stdout and the report to stderr.
+    Migrate(MigrateArgs),
+    /// List the flags which changed between two Bazel versions
+    FlagsDiff(FlagsDiffArgs),
+    /// Print the graph of imports between bazelrc files and of `--config` references
+    ///
+    /// If no <file>s are given, the bazelrc files Bazel reads by default are used.
+    Graph(GraphArgs),
+    /// List all configs, together with their descriptions
+    ///
+    /// The description of a config is taken from the comment right above its first definition.
+    /// If no <file>s are given, the bazelrc files Bazel reads by default are used.
+    Configs(ConfigsArgs),
     /// List supported Bazel versions
     #[clap(hide = true)]
     BazelVersions {},
@@ -101,12 +145,16 @@ async fn main() {
                 client,
                 document_map: Default::default(),
                 bazel_flags,
+                additional_bazel_flags: Default::default(),
                 position_encoding: LspPositionEncoding::UTF16.into(),
                 settings: Settings {
                     format_lines: cli.format_lines.0,
+                    target_bazel_version: None,
+                    additional_bazel_versions: Vec::new(),
                 }
                 .into(),
                 startup_warning: version_message,
+                rc_chains: Default::default(),
             });
             Server::new(stdin, stdout, socket).serve(service).await;
         }
@@ -119,6 +167,24 @@ async fn main() {
         Commands::Lint(args) => {
             handle_lint_cmd(&args, &bazel_flags);
         }
+        Commands::Expand(args) => {
+            handle_expand_cmd(&args, &bazel_flags);
+        }
+        Commands::Flag(args) => {
+            handle_flag_cmd(&args, &bazel_flags);
+        }
+        Commands::Migrate(args) => {
+            handle_migrate_cmd(&args, &bazel_flags);
+        }
+        Commands::FlagsDiff(args) => {
+            handle_flags_diff_cmd(&args);
+        }
+        Commands::Graph(args) => {
+            handle_graph_cmd(&args, &bazel_flags);
+        }
+        Commands::Configs(args) => {
+            handle_configs_cmd(&args, &bazel_flags);
+        }
         Commands::BazelVersions {} => {
             println!(
                 "{}",
@@ -156,9 +222,9 @@ fn load_bazel_flags(cli: &Cli) -> (BazelFlags, Option<String>) {
     }
 }
 
-fn for_each_input_file<CB>(files: &[String], handle_file: CB) -> bool
+fn for_each_input_file<CB>(files: &[String], mut handle_file: CB) -> bool
 where
-    CB: Fn(String, Option<&Path>) -> bool,
+    CB: FnMut(String, Option<&Path>) -> bool,
 {
     let mut had_errors = false;
 
@@ -218,24 +284,168 @@ struct LintArgs {
     /// Suppress output and only indicate errors through the exit code
     #[arg(long, group = "fmt-action")]
     quiet: bool,
+    /// Report flags which are removed, renamed or turned into no-ops in this Bazel version
+    #[arg(long, value_name = "VERSION")]
+    target_bazel_version: Option<String>,
+    /// Also validate against the given Bazel version(s). Can be repeated.
+    #[arg(long = "additional-bazel-version", value_name = "VERSION")]
+    additional_bazel_versions: Vec<String>,
+    /// How to report the found issues
+    #[arg(long, default_value = "text")]
+    output_format: LintOutputFormat,
+    /// Apply all safe fixes, e.g. replacing renamed and abbreviated flags or removing no-ops.
+    /// Unless -i is specified, the fixes are printed as a diff and the remaining issues
+    /// are reported on stderr.
+    #[arg(long)]
+    fix: bool,
+    /// Inplace edit <file>s when applying fixes
+    #[arg(short = 'i', long, requires = "fix")]
+    inplace: bool,
+}
+
+// The maximum number of times we re-lint a file after applying fixes.
+// Applying a fix can make other fixes applicable, e.g. for overlapping fixes.
+const MAX_FIX_ROUNDS: usize = 10;
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum LintOutputFormat {
+    /// Human-readable output
+    Text,
+    /// A JSON array with one entry per issue
+    Json,
+    /// SARIF, e.g. for uploading to GitHub code scanning
+    Sarif,
+    /// GitHub Actions workflow commands, shown as annotations on pull requests
+    Github,
+    /// Checkstyle XML
+    Checkstyle,
 }
 
 fn handle_lint_cmd(args: &LintArgs, bazel_flags: &BazelFlags) {
+    if args.inplace && args.files.is_empty() {
+        let mut cmd = Cli::command();
+        cmd.error(
+            clap::error::ErrorKind::ArgumentConflict,
+            "If the `-i` flag is specified, input file(s) must be specified as part of the command line invocation",
+        ).exit();
+    }
+    // Without `-i`, the fixes are printed as a diff, which can't be combined with machine-readable output
+    let prints_diff = args.fix && !args.inplace;
+    if prints_diff && args.output_format != LintOutputFormat::Text {
+        let mut cmd = Cli::command();
+        cmd.error(
+            clap::error::ErrorKind::ArgumentConflict,
+            "`--fix` without `-i` can only be combined with `--output-format=text`",
+        )
+        .exit();
+    }
+    let additional_bazel_flags = args
+        .additional_bazel_versions
+        .iter()
+        .map(|v| {
+            let (bazel_version, msg) = find_closest_version(AVAILABLE_BAZEL_VERSIONS.as_slice(), v);
+            if let Some(msg) = msg {
+                eprintln!("{}", msg);
+            }
+            load_packaged_bazel_flags(&bazel_version)
+        })
+        .collect::<Vec<_>>();
+    let all_bazel_flags = std::iter::once(bazel_flags)
+        .chain(additional_bazel_flags.iter())
+        .collect::<Vec<_>>();
+    // When printing a diff, the remaining issues are reported on stderr to keep stdout a valid diff
+    let colored = if prints_diff {
+        io::stderr().is_terminal()
+    } else {
+        io::stdout().is_terminal()
+    } && env::var_os("NO_COLOR").is_none();
+    let mut linted_files = Vec::<LintedFile>::new();
+    // The default bazelrc files, loaded once per workspace
+    let mut rc_chains = HashMap::<Option<PathBuf>, RcChain>::new();
     let had_errors = for_each_input_file(&args.files, |input: String, path: Option<&Path>| {
-        let diagnostics =
-            diagnostics_from_string(&input, bazel_flags, path, LspPositionEncoding::UTF32);
-        if !args.quiet {
-            for d in &diagnostics {
-                // TODO: improve printing, either using ariadne or codespan-reporting
-                println!(
-                    "{}: {}",
-                    path.and_then(Path::to_str).unwrap_or("<stdin>"),
-                    d.message
-                );
+        // Diagnostics can only reference other files through absolute paths
+        let absolute_path = path.and_then(|p| std::path::absolute(p).ok());
+        let workspace_path = absolute_path.as_deref().and_then(get_workspace_path);
+        let rc_chain = rc_chains.entry(workspace_path.clone()).or_insert_with(|| {
+            RcChain::load(
+                &get_default_rc_paths(workspace_path.as_deref()),
+                bazel_flags,
+            )
+        });
+        let lint = |text: &str| {
+            merge_diagnostics_for_versions(&all_bazel_flags, |bazel_flags| {
+                diagnostics_from_string(
+                    text,
+                    bazel_flags,
+                    absolute_path.as_deref(),
+                    rc_chain,
+                    args.target_bazel_version.as_deref(),
+                    LspPositionEncoding::UTF32,
+                )
+            })
+        };
+        let display_path = path
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or("<stdin>".to_string());
+        let mut diagnostics = lint(&input);
+        let mut source = input;
+        let mut has_fixes = false;
+        if args.fix {
+            let mut fixed = source.clone();
+            for _ in 0..MAX_FIX_ROUNDS {
+                if apply_preferred_fixes(&mut fixed, &diagnostics, LspPositionEncoding::UTF32) == 0
+                {
+                    break;
+                }
+                diagnostics = lint(&fixed);
             }
+            if args.inplace {
+                if fixed != source {
+                    fs::write(path.unwrap(), &fixed).expect("Failed to write file");
+                }
+            } else {
+                let diff = unified_diff(&source, &fixed, &display_path, &display_path);
+                if !args.quiet {
+                    print!("{}", diff);
+                }
+                has_fixes = !diff.is_empty();
+            }
+            // Report the remaining diagnostics, which refer to the fixed contents
+            source = fixed;
         }
-        !diagnostics.is_empty()
+        // Hints and information, e.g. about overridden settings, don't fail the lint
+        let had_diagnostics = diagnostics.iter().any(|d| {
+            !matches!(
+                d.severity,
+                Some(DiagnosticSeverity::INFORMATION) | Some(DiagnosticSeverity::HINT)
+            )
+        });
+        let linted_file = LintedFile {
+            path: display_path,
+            absolute_path,
+            source,
+            diagnostics,
+        };
+        if args.quiet {
+            // Nothing to print
+        } else if prints_diff {
+            eprint!("{}", format_text(&linted_file, colored));
+        } else if args.output_format == LintOutputFormat::Text {
+            print!("{}", format_text(&linted_file, colored));
+        } else {
+            linted_files.push(linted_file);
+        }
+        had_diagnostics || has_fixes
     });
+    if !args.quiet {
+        match args.output_format {
+            LintOutputFormat::Text => {}
+            LintOutputFormat::Json => println!("{}", format_json(&linted_files)),
+            LintOutputFormat::Sarif => println!("{}", format_sarif(&linted_files)),
+            LintOutputFormat::Github => print!("{}", format_github(&linted_files)),
+            LintOutputFormat::Checkstyle => print!("{}", format_checkstyle(&linted_files)),
+        }
+    }
     if had_errors {
         process::exit(1);
     }
@@ -251,6 +461,9 @@ struct FormatArgs {
     /// Only check if the given file(s) are formatted correctly
     #[arg(long, group = "fmt-action")]
     check: bool,
+    /// Print the necessary changes as a unified diff
+    #[arg(long, group = "fmt-action")]
+    diff: bool,
 }
 
 fn handle_format_cmd(args: &FormatArgs, bazel_flags: &BazelFlags, line_flow: FormatLineFlow) {
@@ -279,6 +492,13 @@ fn handle_format_cmd(args: &FormatArgs, bazel_flags: &BazelFlags, line_flow: For
                     } else {
                         println!("{} is already correctly formatted", input_name);
                     }
+                } else if args.diff {
+                    let input_name = path
+                        .map(|p| p.to_string_lossy().into_owned())
+                        .unwrap_or("<stdin>".to_string());
+                    let diff = unified_diff(&input, &formatted, &input_name, &input_name);
+                    print!("{}", diff);
+                    return !diff.is_empty();
                 } else if args.inplace {
                     fs::write(path.unwrap(), formatted).expect("Failed to write file");
                 } else {
@@ -302,6 +522,409 @@ fn handle_format_cmd(args: &FormatArgs, bazel_flags: &BazelFlags, line_flow: For
     }
 }
 
+// Loads the given bazelrc files, or the ones Bazel reads by default if no files are given
+fn load_rc_files(rc_paths: &[PathBuf], bazel_flags: &BazelFlags) -> Vec<RcFile> {
+    if rc_paths.is_empty() {
+        // Silently skip default files which don't exist, like Bazel does
+        let current_dir = env::current_dir().unwrap();
+        get_default_rc_paths(get_workspace_path(&current_dir).as_deref())
+            .iter()
+            .filter_map(|p| RcFile::load(p, bazel_flags))
+            .collect::<Vec<_>>()
+    } else {
+        rc_paths
+            .iter()
+            .map(|p| {
+                let absolute_path = std::path::absolute(p).unwrap_or(p.clone());
+                RcFile::load(&absolute_path, bazel_flags).unwrap_or_else(|| {
+                    eprintln!("Failed to read `{}`", p.display());
+                    process::exit(1);
+                })
+            })
+            .collect::<Vec<_>>()
+    }
+}
+
+#[derive(Parser)]
+struct ExpandArgs {
+    /// Read the given bazelrc file(s) instead of the default ones. Can be repeated.
+    #[arg(long = "rc", value_name = "PATH")]
+    rc_files: Vec<String>,
+    /// The Bazel command, e.g. `build`
+    command: String,
+    /// The command line arguments, e.g. `--config=ci //my:target`
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    args: Vec<String>,
+}
+
+fn handle_expand_cmd(args: &ExpandArgs, bazel_flags: &BazelFlags) {
+    // `--rc` can also be specified after the command
+    let mut rc_paths = args.rc_files.iter().map(PathBuf::from).collect::<Vec<_>>();
+    let mut command_args = Vec::new();
+    let mut iter = args.args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--rc" {
+            rc_paths.extend(iter.next().map(PathBuf::from));
+        } else if let Some(path) = arg.strip_prefix("--rc=") {
+            rc_paths.push(PathBuf::from(path));
+        } else {
+            command_args.push(arg.clone());
+        }
+    }
+    let current_dir = env::current_dir().unwrap();
+    let rc_files = load_rc_files(&rc_paths, bazel_flags);
+
+    let options = match expand_options(&rc_files, &args.command, &command_args, bazel_flags) {
+        Ok(options) => options,
+        Err(msg) => {
+            eprintln!("{}", msg);
+            process::exit(1);
+        }
+    };
+    let width = options.iter().map(|o| o.option.len()).max().unwrap_or(0);
+    for o in options {
+        let origin = match o.source {
+            Some(source) => format!(
+                "{}:{} ({})",
+                source
+                    .path
+                    .strip_prefix(&current_dir)
+                    .unwrap_or(&source.path)
+                    .display(),
+                source.line_nr,
+                source.rc_command
+            ),
+            None => "command line".to_string(),
+        };
+        println!("{:width$}  # {}", o.option, origin, width = width);
+    }
+}
+
+#[derive(Parser)]
+struct FlagArgs {
+    /// The flag, e.g. `remote_cache`, `--noremote_cache` or `-k`
+    #[arg(allow_hyphen_values = true, required_unless_present = "search")]
+    name: Option<String>,
+    /// List all flags whose name or documentation contains the given text
+    #[arg(long, value_name = "TEXT", conflicts_with = "name")]
+    search: Option<String>,
+}
+
+fn handle_flag_cmd(args: &FlagArgs, bazel_flags: &BazelFlags) {
+    let painter = Painter {
+        colored: io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none(),
+    };
+    if let Some(query) = &args.search {
+        let flags = bazel_flags.search(query);
+        if flags.is_empty() {
+            eprintln!("No flag matches {:?}", query);
+            process::exit(1);
+        }
+        let width = flags.iter().map(|f| f.name.len() + 2).max().unwrap_or(0);
+        for f in flags {
+            // Only show the first line of the documentation
+            let summary = f
+                .documentation
+                .as_deref()
+                .and_then(|d| d.lines().next())
+                .unwrap_or("")
+                .replace("%{product}", "Bazel");
+            let name = format!("--{:width$}", f.name, width = width - 2);
+            println!("{}  {}", painter.paint(BOLD, &name), summary);
+        }
+        return;
+    }
+
+    let name = args.name.as_ref().unwrap();
+    let invocation = if name.starts_with('-') {
+        name.clone()
+    } else {
+        format!("--{}", name)
+    };
+    let Some((_, info)) = bazel_flags.get_by_invocation(&invocation) else {
+        let candidates = bazel_flags.flags_by_name.keys().map(String::as_str);
+        let suggestions = find_similar(invocation.trim_start_matches('-'), candidates, 3)
+            .into_iter()
+            .map(|s| format!("--{}", s))
+            .collect::<Vec<_>>();
+        eprintln!(
+            "Unknown flag {:?}. {}",
+            invocation,
+            format_suggestions(&suggestions)
+        );
+        process::exit(1);
+    };
+
+    print!(
+        "{}",
+        render_markdown(&info.get_documentation_markdown(), &painter)
+    );
+    if let Some(old_name) = &info.old_name {
+        println!("Old name: --{}", old_name);
+    }
+    if let Some(warning) = &info.deprecation_warning {
+        println!("Deprecation warning: {}", warning);
+    }
+    println!("Commands: {}", info.commands.join(", "));
+    let version_ranges = PACKAGED_FLAG_HISTORY
+        .get_version_ranges(&info.name)
+        .into_iter()
+        .map(|(first, last)| {
+            if first == last {
+                first.to_string()
+            } else {
+                format!("{} - {}", first, last)
+            }
+        })
+        .collect::<Vec<_>>();
+    if !version_ranges.is_empty() {
+        println!("Bazel versions: {}", version_ranges.join(", "));
+    }
+}
+
+#[derive(Parser)]
+struct MigrateArgs {
+    /// File(s) to migrate
+    files: Vec<String>,
+    /// The Bazel version the files are currently used with. Defaults to the detected Bazel version.
+    #[arg(long, value_name = "VERSION")]
+    from: Option<String>,
+    /// The Bazel version to migrate to
+    #[arg(long, value_name = "VERSION")]
+    to: String,
+    /// Inplace edit <file>s
+    #[arg(short = 'i', long)]
+    inplace: bool,
+}
+
+fn handle_migrate_cmd(args: &MigrateArgs, bazel_flags: &BazelFlags) {
+    if args.inplace && args.files.is_empty() {
+        let mut cmd = Cli::command();
+        cmd.error(
+            clap::error::ErrorKind::ArgumentConflict,
+            "If the `-i` flag is specified, input file(s) must be specified as part of the command line invocation",
+        ).exit();
+    }
+    let resolve_version = |version: &str| {
+        let (resolved, msg) = find_closest_version(AVAILABLE_BAZEL_VERSIONS.as_slice(), version);
+        if let Some(msg) = msg {
+            eprintln!("{}", msg);
+        }
+        resolved
+    };
+    let Some(from_version) = args.from.as_ref().or(bazel_flags.bazel_version.as_ref()) else {
+        let mut cmd = Cli::command();
+        cmd.error(
+            clap::error::ErrorKind::MissingRequiredArgument,
+            "The current Bazel version is unknown. Please specify it using `--from`",
+        )
+        .exit();
+    };
+    let from_bazel_flags = load_packaged_bazel_flags(&resolve_version(from_version));
+    let to_version = resolve_version(&args.to);
+
+    // Summary counts for renamed, dropped, commented out and unknown flags
+    let mut counts = [0; 4];
+    let had_errors = for_each_input_file(&args.files, |input: String, path: Option<&Path>| {
+        let result = migrate_bazelrc(
+            &input,
+            &from_bazel_flags,
+            &PACKAGED_FLAG_HISTORY,
+            &to_version,
+        );
+        let input_name = path
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or("<stdin>".to_string());
+        let report = result
+            .changes
+            .iter()
+            .map(|c| {
+                let attention = if c.needs_attention() {
+                    " (needs manual attention)"
+                } else {
+                    ""
+                };
+                format!(
+                    "{}:{}: {}{}\n",
+                    input_name,
+                    c.line_nr,
+                    c.describe(&to_version),
+                    attention
+                )
+            })
+            .collect::<String>();
+        for c in &result.changes {
+            counts[match c.action {
+                MigrationAction::Renamed(_) => 0,
+                MigrationAction::DroppedNoOp => 1,
+                MigrationAction::CommentedOut => 2,
+                MigrationAction::Unknown => 3,
+            }] += 1;
+        }
+        if args.inplace {
+            if result.text != input {
+                fs::write(path.unwrap(), &result.text).expect("Failed to write file");
+            }
+            print!("{}", report);
+        } else {
+            // The report goes to stderr, such that stdout only contains the migrated file(s)
+            if let Some(p) = path {
+                println!("--- {} ---", p.to_string_lossy());
+            }
+            print!("{}", result.text);
+            eprint!("{}", report);
+        }
+        false
+    });
+    let [renamed, dropped, commented_out, unknown] = counts;
+    let summary = format!(
+        "Migrating to Bazel {}: Renamed {} flag(s), dropped {} no-op(s), commented out {} removed flag(s). {} flag(s) need manual attention.",
+        to_version,
+        renamed,
+        dropped,
+        commented_out,
+        commented_out + unknown
+    );
+    if args.inplace {
+        println!("{}", summary);
+    } else {
+        eprintln!("{}", summary);
+    }
+    if had_errors {
+        process::exit(1);
+    }
+}
+
+#[derive(Parser)]
+struct FlagsDiffArgs {
+    /// The old Bazel version
+    from: String,
+    /// The new Bazel version
+    to: String,
+    /// Output format
+    #[arg(long, default_value = "text")]
+    format: FlagsDiffOutputFormat,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum FlagsDiffOutputFormat {
+    Text,
+    Json,
+}
+
+fn handle_flags_diff_cmd(args: &FlagsDiffArgs) {
+    let resolve_version = |version: &str| {
+        let (resolved, msg) = find_closest_version(AVAILABLE_BAZEL_VERSIONS.as_slice(), version);
+        if let Some(msg) = msg {
+            eprintln!("{}", msg);
+        }
+        resolved
+    };
+    let from = resolve_version(&args.from);
+    let to = resolve_version(&args.to);
+    let diff = PACKAGED_FLAG_HISTORY.diff_versions(&from, &to);
+    let kind_name = |kind: &FlagDiffKind| match kind {
+        FlagDiffKind::Added => "added",
+        FlagDiffKind::Removed => "removed",
+        FlagDiffKind::Renamed(_) => "renamed",
+        FlagDiffKind::Deprecated => "deprecated",
+        FlagDiffKind::NoOp => "no-op",
+    };
+    match args.format {
+        FlagsDiffOutputFormat::Json => {
+            let entries = diff
+                .iter()
+                .map(|d| {
+                    let mut entry = serde_json::json!({
+                        "flag": d.name,
+                        "change": kind_name(&d.kind),
+                    });
+                    if let FlagDiffKind::Renamed(old_name) = &d.kind {
+                        entry["old_name"] = old_name.clone().into();
+                    }
+                    entry
+                })
+                .collect::<Vec<_>>();
+            println!("{}", serde_json::to_string_pretty(&entries).unwrap());
+        }
+        FlagsDiffOutputFormat::Text => {
+            println!("Flag changes from Bazel {} to {}:", from, to);
+            let mut previous_kind = None;
+            for d in &diff {
+                let kind = kind_name(&d.kind);
+                if previous_kind != Some(kind) {
+                    let count = diff.iter().filter(|o| kind_name(&o.kind) == kind).count();
+                    println!("\n{} ({}):", kind, count);
+                    previous_kind = Some(kind);
+                }
+                match &d.kind {
+                    FlagDiffKind::Renamed(old_name) => println!("  --{} -> --{}", old_name, d.name),
+                    _ => println!("  --{}", d.name),
+                }
+            }
+            if diff.is_empty() {
+                println!("\nNo changes");
+            }
+        }
+    }
+}
+
+#[derive(Parser)]
+struct GraphArgs {
+    /// The bazelrc files
+    files: Vec<PathBuf>,
+    /// Output format
+    #[arg(long, default_value = "dot")]
+    format: GraphFormat,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum GraphFormat {
+    Dot,
+    Json,
+    Mermaid,
+}
+
+fn handle_graph_cmd(args: &GraphArgs, bazel_flags: &BazelFlags) {
+    let rc_files = load_rc_files(&args.files, bazel_flags);
+    let graph = RcGraph::from_rc_files(&rc_files);
+    let current_dir = fs::canonicalize(env::current_dir().unwrap()).unwrap();
+    let output = match args.format {
+        GraphFormat::Dot => graph.to_dot(&current_dir),
+        GraphFormat::Json => graph.to_json(&current_dir) + "\n",
+        GraphFormat::Mermaid => graph.to_mermaid(&current_dir),
+    };
+    print!("{}", output);
+}
+
+#[derive(Parser)]
+struct ConfigsArgs {
+    /// The bazelrc files
+    files: Vec<PathBuf>,
+    /// Output format
+    #[arg(long, default_value = "table")]
+    format: ConfigsFormat,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ConfigsFormat {
+    Table,
+    Json,
+    Markdown,
+}
+
+fn handle_configs_cmd(args: &ConfigsArgs, bazel_flags: &BazelFlags) {
+    let rc_files = load_rc_files(&args.files, bazel_flags);
+    let configs = collect_configs(&rc_files);
+    let current_dir = fs::canonicalize(env::current_dir().unwrap()).unwrap();
+    let output = match args.format {
+        ConfigsFormat::Table => format_configs_table(&configs, &current_dir),
+        ConfigsFormat::Json => format_configs_json(&configs) + "\n",
+        ConfigsFormat::Markdown => format_configs_markdown(&configs, &current_dir),
+    };
+    print!("{}", output);
+}
+
 #[test]
 fn verify_cli() {
     use clap::CommandFactory;
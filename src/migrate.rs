@@ -0,0 +1,243 @@
+use ropey::Rope;
+
+use crate::{
+    bazel_flags::{combine_key_value_flags, BazelFlags, FlagLookupType},
+    diagnostic::{get_flag_removal_span, SKIPPED_PREFIXES},
+    flag_history::{FlagChange, FlagHistory, FlagUpgradeState},
+    parser::{parse_from_str, ParserResult},
+    tokenizer::Span,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MigrationAction {
+    // The flag was replaced by its new name
+    Renamed(String),
+    // The flag became a no-op and was dropped
+    DroppedNoOp,
+    // The flag was removed from Bazel and was commented out
+    CommentedOut,
+    // The flag is unknown in the old Bazel version
+    Unknown,
+}
+
+// A change made (or not made) while migrating a bazelrc file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MigrationChange {
+    // 1-based
+    pub line_nr: usize,
+    pub flag: String,
+    pub action: MigrationAction,
+}
+
+impl MigrationChange {
+    // Changes which the user should double-check
+    pub fn needs_attention(&self) -> bool {
+        matches!(
+            self.action,
+            MigrationAction::CommentedOut | MigrationAction::Unknown
+        )
+    }
+
+    pub fn describe(&self, to_version: &str) -> String {
+        match &self.action {
+            MigrationAction::Renamed(new_name) => {
+                format!("Renamed {:?} to {:?}", self.flag, new_name)
+            }
+            MigrationAction::DroppedNoOp => format!("Dropped no-op {:?}", self.flag),
+            MigrationAction::CommentedOut => format!(
+                "Commented out {:?}, which was removed in Bazel {}",
+                self.flag, to_version
+            ),
+            MigrationAction::Unknown => format!("Unknown flag {:?}", self.flag),
+        }
+    }
+}
+
+pub struct MigrationResult {
+    pub text: String,
+    pub changes: Vec<MigrationChange>,
+}
+
+// Rewrites a bazelrc file written for `from_bazel_flags` for the Bazel version `to_version`:
+// Renamed flags are replaced by their new names, flags which became no-ops are dropped
+// and removed flags are commented out.
+pub fn migrate_bazelrc(
+    text: &str,
+    from_bazel_flags: &BazelFlags,
+    history: &FlagHistory,
+    to_version: &str,
+) -> MigrationResult {
+    let rope = Rope::from_str(text);
+    let ParserResult {
+        tokens: _,
+        mut lines,
+        errors: _,
+    } = parse_from_str(text);
+    combine_key_value_flags(&mut lines, from_bazel_flags);
+
+    let mut edits = Vec::<(Span, String)>::new();
+    let mut changes = Vec::<MigrationChange>::new();
+    for line in &lines {
+        let Some(command) = &line.command else {
+            continue;
+        };
+        let line_start = rope.line_to_byte(rope.byte_to_line(line.span.start));
+        let line_nr = rope.byte_to_line(line.span.start) + 1;
+        let rc_command = match &line.config {
+            Some(config) => format!("{}:{}", command.0, config.0),
+            None => command.0.clone(),
+        };
+        let mut line_edits = Vec::<(Span, String)>::new();
+        let mut comments = String::new();
+        let mut removed_flags = 0;
+        for (flag_nr, flag) in line.flags.iter().enumerate() {
+            let Some(name) = &flag.name else {
+                continue;
+            };
+            if SKIPPED_PREFIXES.iter().any(|p| name.0.starts_with(p)) {
+                continue;
+            }
+            let Some((lookup_type, info)) = from_bazel_flags.get_by_invocation(&name.0) else {
+                changes.push(MigrationChange {
+                    line_nr,
+                    flag: name.0.clone(),
+                    action: MigrationAction::Unknown,
+                });
+                continue;
+            };
+            let used_name = match lookup_type {
+                FlagLookupType::OldName => info.old_name.as_ref().unwrap(),
+                _ => &info.name,
+            };
+            let Some(FlagChange { state, .. }) = history.find_upgrade_change(
+                used_name,
+                from_bazel_flags.bazel_version.as_deref(),
+                to_version,
+            ) else {
+                continue;
+            };
+            let flag_end = flag.value.as_ref().unwrap_or(name).1.end;
+            let action = match state {
+                FlagUpgradeState::Renamed(new_name) => {
+                    let new_text = if info.is_negated_by(&name.0) {
+                        format!("--no{}", new_name)
+                    } else {
+                        format!("--{}", new_name)
+                    };
+                    line_edits.push((name.1.clone(), new_text.clone()));
+                    MigrationAction::Renamed(new_text)
+                }
+                FlagUpgradeState::NoOp => {
+                    line_edits.push((get_flag_removal_span(&rope, line, flag_nr), String::new()));
+                    removed_flags += 1;
+                    MigrationAction::DroppedNoOp
+                }
+                FlagUpgradeState::Missing => {
+                    line_edits.push((get_flag_removal_span(&rope, line, flag_nr), String::new()));
+                    removed_flags += 1;
+                    comments += &format!(
+                        "# {} {} # removed in {}\n",
+                        rc_command,
+                        &text[name.1.start..flag_end],
+                        to_version
+                    );
+                    MigrationAction::CommentedOut
+                }
+            };
+            changes.push(MigrationChange {
+                line_nr,
+                flag: name.0.clone(),
+                action,
+            });
+        }
+        if removed_flags > 0 && removed_flags == line.flags.len() && line.comment.is_none() {
+            // Nothing is left of this line. Replace it by the comments.
+            let last_flag = line.flags.last().unwrap();
+            let end = last_flag
+                .value
+                .as_ref()
+                .or(last_flag.name.as_ref())
+                .unwrap()
+                .1
+                .end;
+            let next_line = (rope.byte_to_line(end) + 1).min(rope.len_lines());
+            edits.push((line_start..rope.line_to_byte(next_line), comments));
+        } else {
+            if !comments.is_empty() {
+                edits.push((line_start..line_start, comments));
+            }
+            edits.extend(line_edits);
+        }
+    }
+
+    // Apply the edits back to front, so that the offsets stay valid
+    let mut result = text.to_string();
+    edits.sort_by_key(|(span, _)| std::cmp::Reverse(span.start));
+    for (span, new_text) in edits {
+        result.replace_range(span, &new_text);
+    }
+    MigrationResult {
+        text: result,
+        changes,
+    }
+}
+
+#[test]
+fn test_migrate_bazelrc() {
+    use crate::bazel_flags_proto::{FlagCollection, FlagInfo};
+    let flag = crate::bazel_flags::test_flag_info;
+    let flags = vec![
+        flag("keep_going", &["7.0.0", "8.0.0"]),
+        flag("removed", &["7.0.0"]),
+        flag("old_name", &["7.0.0"]),
+        FlagInfo {
+            old_name: Some("old_name".to_string()),
+            ..flag("new_name", &["8.0.0"])
+        },
+        flag("noop", &["7.0.0"]),
+        FlagInfo {
+            effect_tags: vec!["NO_OP".to_string()],
+            ..flag("noop", &["8.0.0"])
+        },
+    ];
+    let history = FlagHistory::from_collection(FlagCollection {
+        flag_infos: flags.clone(),
+        all_bazel_versions: vec!["7.0.0".to_string(), "8.0.0".to_string()],
+    });
+    let from_bazel_flags = BazelFlags::from_flags(flags, Some("7.0.0"));
+    let result = migrate_bazelrc(
+        "build --keep_going --noold_name --noop --typo
+build:ci --removed=1 --noop
+build --keep_going --removed # comment
+test --//my:setting
+",
+        &from_bazel_flags,
+        &history,
+        "8.0.0",
+    );
+    assert_eq!(
+        result.text,
+        "build --keep_going --nonew_name --typo
+# build:ci --removed=1 # removed in 8.0.0
+# build --removed # removed in 8.0.0
+build --keep_going # comment
+test --//my:setting
+"
+    );
+    let descriptions = result
+        .changes
+        .iter()
+        .map(|c| format!("{}: {}", c.line_nr, c.describe("8.0.0")))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        descriptions,
+        vec![
+            "1: Renamed \"--noold_name\" to \"--nonew_name\"",
+            "1: Dropped no-op \"--noop\"",
+            "1: Unknown flag \"--typo\"",
+            "2: Commented out \"--removed\", which was removed in Bazel 8.0.0",
+            "2: Dropped no-op \"--noop\"",
+            "3: Commented out \"--removed\", which was removed in Bazel 8.0.0",
+        ]
+    );
+}
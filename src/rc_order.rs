@@ -0,0 +1,523 @@
+use std::{
+    collections::{HashMap, HashSet},
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use ropey::Rope;
+
+use crate::{
+    bazel_flags::{combine_key_value_flags, BazelFlags},
+    imports::{
+        canonicalize_path, get_import_path, load_imported_files, ImportedFile, ImportedFiles,
+    },
+    parser::{parse_from_str, Line, ParserResult},
+};
+
+// The command from which a command inherits its options, e.g. `test` also applies `build` options
+fn get_parent_command(command: &str) -> Option<&'static str> {
+    match command {
+        "aquery" | "clean" | "config" | "info" | "mobile-install" | "print_action" | "run"
+        | "test" => Some("build"),
+        "coverage" | "cquery" => Some("test"),
+        _ => None,
+    }
+}
+
+// The commands whose bazelrc lines apply to `command`, in the order in which Bazel applies them.
+// Later commands in the chain take precedence.
+pub fn get_command_chain(command: &str) -> Vec<&str> {
+    if command == "startup" {
+        return vec!["startup"];
+    }
+    let mut chain = vec![command];
+    let mut current = command;
+    while let Some(parent) = get_parent_command(current) {
+        chain.push(parent);
+        current = parent;
+    }
+    chain.extend(["common", "always"]);
+    chain.reverse();
+    chain
+}
+
+// A line of a bazelrc file or one of its imports
+pub struct RcLine<'a> {
+    // The imported file containing this line, or `None` for the root file
+    pub file: Option<&'a ImportedFile>,
+    pub line: &'a Line,
+}
+
+// Lists the lines of a bazelrc file in the order in which Bazel reads them,
+// i.e. with the lines of imported files inlined at the `import` statement
+pub fn flatten_imports<'a>(lines: &'a [Line], imported: &'a ImportedFiles) -> Vec<RcLine<'a>> {
+    let mut result = Vec::new();
+    let mut active = HashSet::<&Path>::new();
+    if let Some(root_path) = &imported.root_path {
+        active.insert(root_path);
+    }
+    flatten_imports_into(lines, None, imported, &mut active, &mut result);
+    result
+}
+
+fn flatten_imports_into<'a>(
+    lines: &'a [Line],
+    file: Option<&'a ImportedFile>,
+    imported: &'a ImportedFiles,
+    // The files currently being flattened, used to break import cycles
+    active: &mut HashSet<&'a Path>,
+    result: &mut Vec<RcLine<'a>>,
+) {
+    let file_path = file
+        .map(|f| f.path.as_path())
+        .or(imported.root_path.as_deref());
+    for line in lines {
+        result.push(RcLine { file, line });
+        let Some(raw_path) = get_import_path(line) else {
+            continue;
+        };
+        let Some(imported_file) = imported
+            .imports
+            .iter()
+            .find(|i| Some(i.from.as_path()) == file_path && i.span == raw_path.1)
+            .and_then(|i| imported.get_file(&i.path))
+        else {
+            continue;
+        };
+        if active.insert(&imported_file.path) {
+            flatten_imports_into(
+                &imported_file.lines,
+                Some(imported_file),
+                imported,
+                active,
+                result,
+            );
+            active.remove(imported_file.path.as_path());
+        }
+    }
+}
+
+// The bazelrc files which Bazel reads by default, in the order in which it reads them.
+// See https://bazel.build/run/bazelrc#bazelrc-file-locations
+pub fn get_default_rc_paths(workspace_path: Option<&Path>) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    if cfg!(windows) {
+        paths.extend(env::var_os("ProgramData").map(|p| Path::new(&p).join("bazel.bazelrc")));
+    } else {
+        paths.push(PathBuf::from("/etc/bazel.bazelrc"));
+    }
+    paths.extend(workspace_path.map(|p| p.join(".bazelrc")));
+    let home = env::var_os(if cfg!(windows) { "USERPROFILE" } else { "HOME" });
+    paths.extend(home.map(|p| Path::new(&p).join(".bazelrc")));
+    paths
+}
+
+// A bazelrc file, together with all the files it imports
+#[derive(Debug)]
+pub struct RcFile {
+    pub path: PathBuf,
+    pub rope: Rope,
+    pub lines: Vec<Line>,
+    pub imported: ImportedFiles,
+}
+
+impl RcFile {
+    pub fn parse(path: &Path, contents: &str, bazel_flags: &BazelFlags) -> RcFile {
+        let ParserResult {
+            tokens: _,
+            mut lines,
+            errors: _,
+        } = parse_from_str(contents);
+        combine_key_value_flags(&mut lines, bazel_flags);
+        let imported = load_imported_files(&lines, Some(path), bazel_flags);
+        RcFile {
+            path: path.to_path_buf(),
+            rope: Rope::from_str(contents),
+            lines,
+            imported,
+        }
+    }
+
+    pub fn load(path: &Path, bazel_flags: &BazelFlags) -> Option<RcFile> {
+        let contents = fs::read_to_string(path).ok()?;
+        Some(RcFile::parse(path, &contents, bazel_flags))
+    }
+}
+
+// The bazelrc files which Bazel reads by default for a workspace, together with their imports.
+// Loaded once per workspace, such that other files can be diagnosed in their context.
+#[derive(Debug, Default)]
+pub struct RcChain {
+    // The default bazelrc files and all files they import, including missing ones
+    pub files: HashSet<PathBuf>,
+    // The files which are `try-import`ed, directly or transitively
+    pub try_imported: HashSet<PathBuf>,
+    // The files defining each config
+    pub configs: HashMap<String, HashSet<PathBuf>>,
+    // Set if one of the files has imports which could not be loaded
+    pub has_missing_imports: bool,
+    // The default bazelrc files which exist, in the order in which Bazel reads them
+    pub rc_files: Vec<RcFile>,
+}
+
+// A line of a file in an `RcChain`
+pub struct ChainLine<'a> {
+    pub path: &'a Path,
+    pub rope: &'a Rope,
+    pub line: &'a Line,
+}
+
+impl RcChain {
+    // Loads the given bazelrc files, usually the ones from `get_default_rc_paths`
+    pub fn load(rc_paths: &[PathBuf], bazel_flags: &BazelFlags) -> RcChain {
+        let mut chain = RcChain::default();
+        for path in rc_paths {
+            let Some(rc_file) = RcFile::load(path, bazel_flags) else {
+                // Remember missing files, such that creating them invalidates the chain
+                chain.files.insert(path.clone());
+                continue;
+            };
+            let root_path = rc_file.imported.root_path.clone().unwrap_or(path.clone());
+            chain.files.insert(root_path.clone());
+            for import in &rc_file.imported.imports {
+                chain.files.insert(import.path.clone());
+                if import.optional {
+                    chain.try_imported.insert(import.path.clone());
+                }
+            }
+            chain.has_missing_imports |= rc_file.imported.has_missing_imports;
+            let files = std::iter::once((&root_path, &rc_file.lines))
+                .chain(rc_file.imported.files.iter().map(|f| (&f.path, &f.lines)));
+            for (file_path, lines) in files {
+                for line in lines.iter().filter(|l| l.command.is_some()) {
+                    if let Some(config) = &line.config {
+                        chain
+                            .configs
+                            .entry(config.0.clone())
+                            .or_default()
+                            .insert(file_path.clone());
+                    }
+                }
+            }
+            chain.rc_files.push(rc_file);
+        }
+        chain
+    }
+
+    // The lines which Bazel reads after the last line of `path`, e.g. the lines of `~/.bazelrc`
+    // for the workspace's `.bazelrc`. Empty if `path` is not part of the chain.
+    pub fn lines_after(&self, path: &Path) -> Vec<ChainLine<'_>> {
+        let path = canonicalize_path(path.to_path_buf());
+        let mut result = Vec::new();
+        let mut found = false;
+        for rc_file in &self.rc_files {
+            let root_path = rc_file
+                .imported
+                .root_path
+                .as_deref()
+                .unwrap_or(&rc_file.path);
+            let lines = flatten_imports(&rc_file.lines, &rc_file.imported)
+                .into_iter()
+                .map(|l| match l.file {
+                    Some(f) => ChainLine {
+                        path: &f.path,
+                        rope: &f.rope,
+                        line: l.line,
+                    },
+                    None => ChainLine {
+                        path: root_path,
+                        rope: &rc_file.rope,
+                        line: l.line,
+                    },
+                })
+                .collect::<Vec<_>>();
+            match lines.iter().rposition(|l| l.path == path) {
+                Some(last) => {
+                    found = true;
+                    result = lines.into_iter().skip(last + 1).collect();
+                }
+                None if found => result.extend(lines),
+                None => {}
+            }
+        }
+        result
+    }
+
+    // Files which are `try-import`ed usually contain user-specific settings and are not checked in
+    pub fn is_try_imported(&self, path: &Path) -> bool {
+        self.try_imported
+            .contains(&canonicalize_path(path.to_path_buf()))
+    }
+
+    pub fn contains_file(&self, path: &Path) -> bool {
+        self.files.contains(&canonicalize_path(path.to_path_buf()))
+    }
+
+    // Checks if a config is defined in any file of the chain besides `except_path`.
+    // The excluded file is usually the one being edited, whose contents on disk might be outdated.
+    pub fn defines_config(&self, name: &str, except_path: Option<&Path>) -> bool {
+        self.configs
+            .get(name)
+            .is_some_and(|files| files.iter().any(|f| Some(f.as_path()) != except_path))
+    }
+}
+
+// The bazelrc line from which an option originates
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OptionSource {
+    pub path: PathBuf,
+    // 1-based
+    pub line_nr: usize,
+    // The command and config of the line, e.g. `build:ci`
+    pub rc_command: String,
+}
+
+// An option as applied by Bazel
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExpandedOption {
+    // The option, e.g. `--jobs=4`
+    pub option: String,
+    // The bazelrc line defining the option. `None` for options from the command line
+    pub source: Option<OptionSource>,
+}
+
+struct OptionExpander<'a> {
+    // All bazelrc lines, in the order in which Bazel reads them
+    lines: Vec<(OptionSource, &'a Line)>,
+    command: &'a str,
+    chain: Vec<&'a str>,
+    bazel_flags: &'a BazelFlags,
+    // The configs currently being expanded, used to detect cycles
+    active_configs: Vec<String>,
+}
+
+impl OptionExpander<'_> {
+    // Adds the options from all lines for the given config, or the lines without config
+    fn expand_config(
+        &mut self,
+        config: Option<&str>,
+        result: &mut Vec<ExpandedOption>,
+    ) -> Result<(), String> {
+        if let Some(config) = config {
+            if self.active_configs.iter().any(|c| c == config) {
+                return Err(format!(
+                    "Config expansion has a cycle: {} -> {}",
+                    self.active_configs.join(" -> "),
+                    config
+                ));
+            }
+            let is_defined = self
+                .lines
+                .iter()
+                .any(|(_, l)| l.config.as_ref().map(|c| c.0.as_str()) == Some(config));
+            if !is_defined {
+                return Err(format!(
+                    "Config value {:?} is not defined in any bazelrc file",
+                    config
+                ));
+            }
+            self.active_configs.push(config.to_string());
+        }
+        // Bazel applies the lines command by command, and each command in the order of the files
+        for command in self.chain.clone() {
+            for i in 0..self.lines.len() {
+                let (source, line) = &self.lines[i];
+                if line.command.as_ref().map(|c| c.0.as_str()) != Some(command)
+                    || line.config.as_ref().map(|c| c.0.as_str()) != config
+                {
+                    continue;
+                }
+                let (source, line) = (source.clone(), *line);
+                for flag in &line.flags {
+                    let Some(name) = &flag.name else {
+                        continue;
+                    };
+                    if name.0 == "--config" {
+                        if let Some(value) = &flag.value {
+                            self.expand_config(Some(&value.0), result)?;
+                        }
+                        continue;
+                    }
+                    // `common` lines skip flags which are not supported by the command
+                    let is_unsupported = self
+                        .bazel_flags
+                        .get_by_invocation(&name.0)
+                        .is_some_and(|(_, info)| !info.supports_command(self.command));
+                    if command == "common" && is_unsupported {
+                        continue;
+                    }
+                    let option = match &flag.value {
+                        Some(value) => format!("{}={}", name.0, value.0),
+                        None => name.0.clone(),
+                    };
+                    result.push(ExpandedOption {
+                        option,
+                        source: Some(source.clone()),
+                    });
+                }
+            }
+        }
+        if config.is_some() {
+            self.active_configs.pop();
+        }
+        Ok(())
+    }
+}
+
+// Computes the options Bazel applies when invoking `command` with the given command line arguments.
+// Options from the bazelrc files come first, followed by the command line options.
+// `--config` options are replaced by their expansion.
+pub fn expand_options(
+    rc_files: &[RcFile],
+    command: &str,
+    args: &[String],
+    bazel_flags: &BazelFlags,
+) -> Result<Vec<ExpandedOption>, String> {
+    let mut lines = Vec::new();
+    for rc_file in rc_files {
+        for rc_line in flatten_imports(&rc_file.lines, &rc_file.imported) {
+            let (path, rope) = match rc_line.file {
+                Some(f) => (&f.path, &f.rope),
+                None => (&rc_file.path, &rc_file.rope),
+            };
+            let Some(rc_command) = &rc_line.line.command else {
+                continue;
+            };
+            let rc_command = match &rc_line.line.config {
+                Some(config) => format!("{}:{}", rc_command.0, config.0),
+                None => rc_command.0.clone(),
+            };
+            let source = OptionSource {
+                path: path.clone(),
+                line_nr: rope.byte_to_line(rc_line.line.span.start) + 1,
+                rc_command,
+            };
+            lines.push((source, rc_line.line));
+        }
+    }
+    let mut expander = OptionExpander {
+        lines,
+        command,
+        chain: get_command_chain(command),
+        bazel_flags,
+        active_configs: Vec::new(),
+    };
+    let mut result = Vec::new();
+    expander.expand_config(None, &mut result)?;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            // Only target patterns follow
+            break;
+        } else if let Some(config) = arg.strip_prefix("--config=") {
+            expander.expand_config(Some(config), &mut result)?;
+        } else if arg == "--config" {
+            let config = args.next().ok_or("Missing value for \"--config\"")?;
+            expander.expand_config(Some(config), &mut result)?;
+        } else if arg.starts_with('-') {
+            result.push(ExpandedOption {
+                option: arg.clone(),
+                source: None,
+            });
+        }
+    }
+    Ok(result)
+}
+
+#[test]
+fn test_get_command_chain() {
+    assert_eq!(get_command_chain("startup"), vec!["startup"]);
+    assert_eq!(
+        get_command_chain("query"),
+        vec!["always", "common", "query"]
+    );
+    assert_eq!(
+        get_command_chain("coverage"),
+        vec!["always", "common", "build", "test", "coverage"]
+    );
+    // `info`, `clean` and `config` also inherit from `build`
+    for command in ["info", "clean", "config"] {
+        assert_eq!(
+            get_command_chain(command),
+            vec!["always", "common", "build", command]
+        );
+    }
+}
+
+#[test]
+fn test_expand_options() {
+    let bazel_flags = crate::bazel_flags::load_packaged_bazel_flags("8.0.0");
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("examples")
+        .join("expand.bazelrc");
+    let rc_file = RcFile::parse(
+        &path,
+        "import %workspace%/file2.bazelrc
+build --jobs=4
+test --test_output=errors
+common --order_output=full
+build:ci --config=remote --keep_going
+build:remote --remote_cache=grpc://cache
+test:ci --test_output=all
+build:cycle --config=cycle
+",
+        &bazel_flags,
+    );
+    let expand = |command: &str, args: &[&str]| {
+        let args = args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        expand_options(std::slice::from_ref(&rc_file), command, &args, &bazel_flags).map(
+            |options| {
+                options
+                    .into_iter()
+                    .map(|o| match o.source {
+                        Some(s) => format!("{} ({}:{})", o.option, s.rc_command, s.line_nr),
+                        None => o.option,
+                    })
+                    .collect::<Vec<_>>()
+            },
+        )
+    };
+    assert_eq!(
+        expand("test", &["--config=ci", "--jobs=8", "//my:target"]),
+        Ok(vec![
+            // Imported from `file2.bazelrc`
+            "--keep_going (common:2)".to_string(),
+            "--jobs=4 (build:2)".to_string(),
+            "--test_output=errors (test:3)".to_string(),
+            "--remote_cache=grpc://cache (build:remote:6)".to_string(),
+            "--keep_going (build:ci:5)".to_string(),
+            "--test_output=all (test:ci:7)".to_string(),
+            "--jobs=8".to_string(),
+        ])
+    );
+    // `common` lines skip unsupported flags, and `test` lines don't apply to `build`
+    assert_eq!(
+        expand("build", &[]),
+        Ok(vec![
+            "--keep_going (common:2)".to_string(),
+            "--jobs=4 (build:2)".to_string(),
+        ])
+    );
+    assert_eq!(
+        expand("build", &["--config", "typo"]),
+        Err("Config value \"typo\" is not defined in any bazelrc file".to_string())
+    );
+    assert_eq!(
+        expand("build", &["--config=cycle"]),
+        Err("Config expansion has a cycle: cycle -> cycle".to_string())
+    );
+}
+
+#[test]
+fn test_rc_chain() {
+    let bazel_flags = crate::bazel_flags::load_packaged_bazel_flags("8.0.0");
+    let examples =
+        std::fs::canonicalize(Path::new(env!("CARGO_MANIFEST_DIR")).join("examples")).unwrap();
+    let chain = RcChain::load(&[examples.join(".bazelrc")], &bazel_flags);
+    assert!(chain.contains_file(&examples.join(".bazelrc")));
+    assert!(chain.is_try_imported(&examples.join("user_paths.bazelrc")));
+    assert!(!chain.is_try_imported(&examples.join("file2.bazelrc")));
+    assert!(chain.defines_config("examples", None));
+    assert!(!chain.defines_config("examples", Some(&examples.join(".bazelrc"))));
+    assert!(!chain.defines_config("no_such_config", None));
+}
@@ -0,0 +1,106 @@
+// The edit distance between two strings, counting insertions, deletions,
+// substitutions and transpositions of adjacent characters as a single edit each
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a_chars = a.chars().collect::<Vec<_>>();
+    let b_chars = b.chars().collect::<Vec<_>>();
+    let mut d = vec![vec![0; b_chars.len() + 1]; a_chars.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a_chars.len() {
+        for j in 1..=b_chars.len() {
+            let substitution_cost = usize::from(a_chars[i - 1] != b_chars[j - 1]);
+            d[i][j] = (d[i - 1][j - 1] + substitution_cost)
+                .min(d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1);
+            if i > 1
+                && j > 1
+                && a_chars[i - 1] == b_chars[j - 2]
+                && a_chars[i - 2] == b_chars[j - 1]
+            {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a_chars.len()][b_chars.len()]
+}
+
+// Find the candidates most similar to `name`, e.g. to suggest a fix for a typo.
+//
+// Candidates are considered similar if they are within a small edit distance
+// or if they start with `name`. The best matches are returned first.
+pub fn find_similar<'a>(
+    name: &str,
+    candidates: impl Iterator<Item = &'a str>,
+    max_results: usize,
+) -> Vec<&'a str> {
+    let max_distance = (name.chars().count() / 4).max(1);
+    let mut matches = candidates
+        .filter_map(|candidate| {
+            let distance = edit_distance(name, candidate);
+            if distance <= max_distance {
+                Some((distance, candidate))
+            } else if name.len() >= 4 && candidate.starts_with(name) {
+                // Rank prefix matches after all typo matches
+                Some((max_distance + 1, candidate))
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    matches.sort();
+    matches.dedup();
+    matches
+        .into_iter()
+        .take(max_results)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+// Formats a "Did you mean ...?" message
+pub fn format_suggestions(suggestions: &[String]) -> String {
+    let quoted = suggestions
+        .iter()
+        .map(|s| format!("{:?}", s))
+        .collect::<Vec<_>>();
+    match quoted.as_slice() {
+        [] => String::new(),
+        [single] => format!("Did you mean {}?", single),
+        [init @ .., last] => format!("Did you mean {} or {}?", init.join(", "), last),
+    }
+}
+
+#[test]
+fn test_edit_distance() {
+    assert_eq!(edit_distance("", ""), 0);
+    assert_eq!(edit_distance("abc", ""), 3);
+    assert_eq!(edit_distance("remote_chache", "remote_cache"), 1);
+    assert_eq!(edit_distance("built", "build"), 1);
+    assert_eq!(edit_distance("biuld", "build"), 1);
+    assert_eq!(edit_distance("kitten", "sitting"), 3);
+}
+
+#[test]
+fn test_find_similar() {
+    let candidates = [
+        "remote_cache",
+        "remote_cache_compression",
+        "disk_cache",
+        "build",
+    ];
+    assert_eq!(
+        find_similar("remote_chache", candidates.into_iter(), 3),
+        vec!["remote_cache"]
+    );
+    assert_eq!(
+        find_similar("remote_cach", candidates.into_iter(), 3),
+        vec!["remote_cache", "remote_cache_compression"]
+    );
+    assert_eq!(
+        find_similar("foobar", candidates.into_iter(), 3),
+        Vec::<&str>::new()
+    );
+}
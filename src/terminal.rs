@@ -0,0 +1,62 @@
+// ANSI escape codes for colored terminal output
+pub const RESET: &str = "\x1b[0m";
+pub const BOLD: &str = "\x1b[1m";
+pub const RED: &str = "\x1b[1;31m";
+pub const YELLOW: &str = "\x1b[1;33m";
+pub const CYAN: &str = "\x1b[1;36m";
+pub const BLUE: &str = "\x1b[1;34m";
+
+pub struct Painter {
+    pub colored: bool,
+}
+
+impl Painter {
+    pub fn paint(&self, style: &str, text: &str) -> String {
+        if self.colored {
+            format!("{}{}{}", style, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+// Renders the Markdown we generate for hovers for display in a terminal.
+// Only supports backslash escapes and `code` spans, which are highlighted.
+pub fn render_markdown(markdown: &str, painter: &Painter) -> String {
+    let mut result = String::with_capacity(markdown.len());
+    let mut code = None::<String>;
+    let mut chars = markdown.chars();
+    while let Some(c) = chars.next() {
+        match (c, &mut code) {
+            ('`', None) => code = Some(String::new()),
+            ('`', Some(text)) => {
+                result += &painter.paint(CYAN, text);
+                code = None;
+            }
+            (c, Some(text)) => text.push(c),
+            ('\\', None) => result.extend(chars.next()),
+            (c, None) => result.push(c),
+        }
+    }
+    if let Some(text) = code {
+        // Unterminated code span
+        result.push('`');
+        result += &text;
+    }
+    result
+}
+
+#[test]
+fn test_render_markdown() {
+    let painter = Painter { colored: false };
+    assert_eq!(
+        render_markdown("`--config` [`-c`]\n\nSee \\<foo\\> \\_bar\\_", &painter),
+        "--config [-c]\n\nSee <foo> _bar_"
+    );
+    assert_eq!(render_markdown("a `b", &painter), "a `b");
+    let colored = Painter { colored: true };
+    assert_eq!(
+        render_markdown("`--x`", &colored),
+        format!("{}--x{}", CYAN, RESET)
+    );
+}